@@ -0,0 +1,44 @@
+//! A benchmark for the lexer over a large, generated source file.
+//!
+//! Run with `cargo bench --bench lexer`.
+
+use std::hint::black_box;
+
+use alpaca::lexer::Lexer;
+use alpaca::tokens::TokenKind;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+fn generate_source(functions: usize) -> String {
+    let mut source = String::new();
+
+    for i in 0..functions {
+        source.push_str(&format!(
+            "fun function_{i}(first, second) do\n    let value = first + second * {i}\n    if value >= 100 do\n        return value\n    else\n        return \"too small\"\n    end\nend\n\n"
+        ));
+    }
+
+    source
+}
+
+fn lex_all(source: &str) -> usize {
+    let mut lexer = Lexer::new(source);
+    let mut count = 0;
+
+    while lexer.next_token().0 != TokenKind::EoF {
+        count += 1;
+    }
+
+    count
+}
+
+fn bench_lexer(c: &mut Criterion) {
+    let source = generate_source(5_000);
+
+    let mut group = c.benchmark_group("lexer");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("lex_all", |b| b.iter(|| lex_all(black_box(&source))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_lexer);
+criterion_main!(benches);
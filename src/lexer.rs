@@ -1,17 +1,29 @@
 //! The lexer takes some source string and generates a stream of
 //! `TokenKind`'s. A token is any meaningful "word" or "character",
 //! in the sense that items akin to whitespace and comments are filtered out.
+//!
+//! Tools which need to reproduce the source, like formatters, can opt into
+//! keeping whitespace and comments as `Trivia` attached to each token.
 
-use std::{iter::Peekable, str::Chars};
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{iter::Peekable, str::Chars};
 
-use crate::span::{Span, Spanned};
+use crate::span::{line_col, Span, Spanned};
 use crate::tokens::TokenKind;
 
 use unicode_xid::UnicodeXID;
 
-fn get_keyword(name: &str) -> TokenKind {
+fn get_keyword(name: &str) -> TokenKind<'_> {
     match name {
         "and" => TokenKind::And,
+        "assert" => TokenKind::Assert,
+        "break" => TokenKind::Break,
+        "continue" => TokenKind::Continue,
         "do" => TokenKind::Do,
         "else" => TokenKind::Else,
         "end" => TokenKind::End,
@@ -20,12 +32,124 @@ fn get_keyword(name: &str) -> TokenKind {
         "fun" => TokenKind::Fun,
         "if" => TokenKind::If,
         "let" => TokenKind::Let,
+        "match" => TokenKind::Match,
+        "mut" => TokenKind::Mut,
         "or" => TokenKind::Or,
+        "pub" => TokenKind::Pub,
+        "ref" => TokenKind::Ref,
         "return" => TokenKind::Return,
         "true" => TokenKind::True,
         "type" => TokenKind::Type,
+        "where" => TokenKind::Where,
         "while" => TokenKind::While,
-        _ => TokenKind::Ident(name.to_string()),
+        _ => TokenKind::Ident(Cow::Borrowed(name)),
+    }
+}
+
+/// Checks whether `c` ends a line. Besides `\n` and `\r`, Unicode has its
+/// own line and paragraph separators, and the next line control character.
+pub(crate) const fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{85}' | '\u{2028}' | '\u{2029}')
+}
+
+/// The length in bytes of the block comment at the start of `rest`, up to
+/// and including its closing `*/`, or `None` if it's never closed.
+fn block_comment_len(rest: &str) -> Option<usize> {
+    rest[2..].find("*/").map(|i| i + 4)
+}
+
+/// Checks whether the block comment at the start of `rest` is a doc comment.
+/// `/**/` is an empty ordinary comment, rather than an unclosed doc comment.
+fn is_doc_block(rest: &str) -> bool {
+    rest.starts_with("/**") && !rest.starts_with("/**/")
+}
+
+/// Every punctuation mark and operator, ordered from longest to shortest so
+/// that the first match is the longest one.
+const OPERATORS: &[(&str, TokenKind<'static>)] = &[
+    ("...", TokenKind::Ellipsis),
+    ("..=", TokenKind::DotDotEqual),
+    ("::", TokenKind::ColonColon),
+    ("->", TokenKind::Arrow),
+    ("..", TokenKind::DotDot),
+    ("==", TokenKind::EqualEqual),
+    ("!=", TokenKind::BangEqual),
+    (">=", TokenKind::GreaterEqual),
+    (">>", TokenKind::GreaterGreater),
+    ("<=", TokenKind::LessEqual),
+    ("<<", TokenKind::LessLess),
+    ("**", TokenKind::StarStar),
+    ("//", TokenKind::SlashSlash),
+    ("|>", TokenKind::Pipe),
+    ("(", TokenKind::OpenParen),
+    (")", TokenKind::CloseParen),
+    ("[", TokenKind::OpenBracket),
+    ("]", TokenKind::CloseBracket),
+    ("{", TokenKind::OpenBrace),
+    ("}", TokenKind::CloseBrace),
+    (",", TokenKind::Comma),
+    (";", TokenKind::Semicolon),
+    (".", TokenKind::Dot),
+    (":", TokenKind::Colon),
+    ("=", TokenKind::Equal),
+    ("!", TokenKind::Bang),
+    (">", TokenKind::Greater),
+    ("<", TokenKind::Less),
+    ("+", TokenKind::Plus),
+    ("-", TokenKind::Minus),
+    ("*", TokenKind::Star),
+    ("/", TokenKind::Slash),
+    ("?", TokenKind::Question),
+];
+
+/// Options controlling how the lexer treats the source.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LexerOptions {
+    /// Keep whitespace and comments as trivia attached to tokens, rather
+    /// than discarding them.
+    pub preserve_trivia: bool,
+}
+
+/// The kinds of trivia, source text which carries no meaning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// A run of whitespace.
+    Whitespace,
+    /// A `#` comment, up to but not including the end of its line, or a
+    /// `/* */` block comment.
+    Comment,
+}
+
+/// A token from `Lexer::next_raw_token`, which keeps trivia as tokens of
+/// its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawToken<'a> {
+    Token(TokenKind<'a>),
+    Trivia(TriviaKind),
+}
+
+/// A piece of whitespace or a comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia<'a> {
+    pub kind: TriviaKind,
+    pub text: &'a str,
+    pub span: Span,
+}
+
+/// The trivia around a token.
+///
+/// A token's trailing trivia runs up to the end of its line, everything
+/// else belongs to the leading trivia of the token after it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TokenTrivia<'a> {
+    pub leading: Vec<Trivia<'a>>,
+    pub trailing: Vec<Trivia<'a>>,
+}
+
+impl TokenTrivia<'_> {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.leading.is_empty() && self.trailing.is_empty()
     }
 }
 
@@ -34,165 +158,760 @@ fn get_keyword(name: &str) -> TokenKind {
 ///
 /// Generates tokens on-demand from teh given source string,
 /// filtering out unnecessary items.
+///
+/// `position` is a byte offset into `source`, so identifiers and numbers
+/// can be sliced straight out of the source instead of being built up
+/// character by character.
+///
+/// The lexer only needs `alloc`, so it's still available with the default
+/// `std` feature turned off.
+///
+/// ```
+/// use alpaca::lexer::Lexer;
+/// use alpaca::tokens::TokenKind;
+///
+/// let mut lexer = Lexer::new("let x = 1");
+/// assert_eq!(lexer.next_token().0, TokenKind::Let);
+/// assert_eq!(lexer.next_token().0, TokenKind::Ident("x".into()));
+/// assert_eq!(lexer.next_token().0, TokenKind::Equal);
+/// assert_eq!(lexer.next_token().0, TokenKind::Integer("1".into()));
+/// assert_eq!(lexer.next_token().0, TokenKind::EoF);
+/// ```
+#[derive(Clone)]
 pub struct Lexer<'a> {
-    source: Peekable<Chars<'a>>,
+    source: &'a str,
+    chars: Peekable<Chars<'a>>,
     position: usize,
     line: u32,
     column: u32,
+    options: LexerOptions,
+    peeked: Option<(Spanned<TokenKind<'a>>, TokenTrivia<'a>)>,
 }
 
 impl<'a> Lexer<'a> {
+    #[must_use]
     pub fn new(source: &'a str) -> Self {
+        Self::with_options(source, LexerOptions::default())
+    }
+
+    #[must_use]
+    pub fn with_options(source: &'a str, options: LexerOptions) -> Self {
         Self {
-            source: source.chars().peekable(),
+            source,
+            chars: source.chars().peekable(),
             position: 0,
             line: 1,
             column: 0,
+            options,
+            peeked: None,
         }
     }
 
-    fn advance(&mut self) -> Option<char> {
-        self.position += 1;
-        self.source.next()
+    /// Starts lexing the source again from the beginning, keeping the
+    /// options the lexer was created with.
+    ///
+    /// ```
+    /// use alpaca::lexer::Lexer;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// fn tokens(lexer: &mut Lexer) -> Vec<TokenKind<'static>> {
+    ///     let mut tokens = Vec::new();
+    ///     loop {
+    ///         match lexer.next_token().0 {
+    ///             TokenKind::EoF => return tokens,
+    ///             token => tokens.push(token.into_owned()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut lexer = Lexer::new("fun f(x) do x + 1 end");
+    /// let first = tokens(&mut lexer);
+    /// lexer.reset();
+    /// assert_eq!(tokens(&mut lexer), first);
+    /// ```
+    pub fn reset(&mut self) {
+        *self = Self::with_options(self.source, self.options);
     }
 
-    fn peek(&mut self) -> Option<&char> {
-        self.source.peek()
+    /// The line the lexer has read up to, counting from 1.
+    ///
+    /// ```
+    /// use alpaca::lexer::Lexer;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let mut lexer = Lexer::new("let x = 1\u{2028}let y = 2");
+    /// while lexer.next_token().0 != TokenKind::EoF {}
+    /// assert_eq!(lexer.line(), 2);
+    /// assert_eq!(lexer.column(), 9);
+    /// ```
+    #[must_use]
+    pub const fn line(&self) -> u32 {
+        self.line
     }
 
-    fn consume(&mut self, expected: char) -> bool {
-        if let Some(c) = self.peek() {
-            if c == &expected {
-                self.advance();
-                return true;
-            }
+    /// The column the lexer has read up to on its current line, counting
+    /// characters from 0.
+    #[must_use]
+    pub const fn column(&self) -> u32 {
+        self.column
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.position += c.len_utf8();
+
+        // `\r\n` is a single line ending, counted at the `\n`.
+        if is_line_terminator(c) && !(c == '\r' && self.chars.peek() == Some(&'\n')) {
+            self.line += 1;
+            self.column = 0;
+        } else {
+            self.column += 1;
         }
 
-        false
+        Some(c)
     }
 
+    fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
+    }
+
+    /// Checks whether the source has run out. A buffered token counts, so
+    /// after peeking the end of the file this is `true`.
+    ///
+    /// ```
+    /// use alpaca::lexer::Lexer;
+    ///
+    /// let mut lexer = Lexer::new("x");
+    /// lexer.peek_token();
+    /// assert!(!lexer.at_end());
+    /// lexer.next_token();
+    /// assert!(lexer.at_end());
+    /// lexer.peek_token();
+    /// assert!(lexer.at_end());
+    /// ```
     pub fn at_end(&mut self) -> bool {
-        self.peek().is_none()
+        match &self.peeked {
+            Some(((token, _), _)) => *token == TokenKind::EoF,
+            None => self.peek().is_none(),
+        }
     }
 
-    fn create_token(&mut self, kind: TokenKind, len: usize) -> Spanned<TokenKind> {
+    fn create_token(&self, kind: TokenKind<'a>, len: usize) -> Spanned<TokenKind<'a>> {
         (kind, Span::from(self.position - len..self.position))
     }
 
-    fn lex_string(&mut self) -> Spanned<TokenKind> {
-        let mut value = String::new();
-
+    /// Lexes a string literal. Braces are doubled to write one, so `{{`
+    /// and `}}` become `{` and `}`.
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use alpaca::lexer::Lexer;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let (token, _) = Lexer::new("\"a {{ b\"").next_token();
+    /// assert_eq!(token, TokenKind::String("a { b".into()));
+    ///
+    /// let (token, _) = Lexer::new("\"}} c\"").next_token();
+    /// assert_eq!(token, TokenKind::String("} c".into()));
+    ///
+    /// // Strings without escapes still borrow from the source.
+    /// let (token, _) = Lexer::new("\"plain\"").next_token();
+    /// assert!(matches!(token, TokenKind::String(Cow::Borrowed("plain"))));
+    /// ```
+    fn lex_string(&mut self, start: usize) -> Spanned<TokenKind<'a>> {
         // Safe to unwrap, && will short-circuit.
         while !self.at_end() && *self.peek().unwrap() != '"' {
-            value.push(self.advance().unwrap());
+            self.advance();
         }
 
-        let len = value.len();
+        // Skip the opening quote. The token itself covers both quotes.
+        let value = &self.source[start + 1..self.position];
         if self.at_end() {
             return self.create_token(
-                TokenKind::Error("Unterminated string literal. Expected closing quote, instead found EoF (End of File)".to_string()), 
-                len
+                TokenKind::Error("Unterminated string literal. Expected closing quote, instead found EoF (End of File)".to_string()),
+                self.position - start,
             );
         }
 
         self.advance(); // Consume closing quote
-        self.create_token(TokenKind::String(value), len)
-    }
 
-    fn lex_number(&mut self, first_char: char) -> Spanned<TokenKind> {
-        let mut value = String::from(first_char);
+        // `{{` and `}}` escape a single brace, keeping braces free for
+        // interpolation. Only strings which use them need a new allocation.
+        let value = if value.contains("{{") || value.contains("}}") {
+            Cow::Owned(value.replace("{{", "{").replace("}}", "}"))
+        } else {
+            Cow::Borrowed(value)
+        };
+        self.create_token(TokenKind::String(value), self.position - start)
+    }
 
+    /// Lexes an integer literal, along with any suffix naming its type.
+    ///
+    /// Letters directly after the digits which can't be a suffix, as in
+    /// `3x`, make the whole literal an error rather than splitting it into
+    /// a number and a name. Suffixes shaped like a type, such as `u7`, are
+    /// left for the parser to check.
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use alpaca::lexer::tokenize;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let tokens: Vec<TokenKind> = tokenize("3x").into_iter().map(|t| t.0).collect();
+    /// assert_eq!(tokens, [TokenKind::Error("Invalid number literal `3x`".to_string())]);
+    ///
+    /// let tokens: Vec<TokenKind> = tokenize("3 x").into_iter().map(|t| t.0).collect();
+    /// assert_eq!(tokens, [TokenKind::Integer("3".into()), TokenKind::Ident("x".into())]);
+    ///
+    /// for source in ["255u8", "3u7"] {
+    ///     let tokens: Vec<TokenKind> = tokenize(source).into_iter().map(|t| t.0).collect();
+    ///     assert_eq!(tokens, [TokenKind::Integer(source.into())]);
+    /// }
+    ///
+    /// // The digits are borrowed from the source rather than copied.
+    /// assert!(matches!(&tokenize("255")[0].0, TokenKind::Integer(Cow::Borrowed("255"))));
+    ///
+    /// // A leading zero is an error suggesting `0o`, but `0` on its own is fine.
+    /// let TokenKind::Error(message) = &tokenize("0123")[0].0 else {
+    ///     panic!("expected an error");
+    /// };
+    /// assert!(message.contains("leading zero") && message.contains("`0o`"));
+    /// assert_eq!(tokenize("0")[0].0, TokenKind::Integer("0".into()));
+    /// ```
+    fn lex_number(&mut self, start: usize) -> Spanned<TokenKind<'a>> {
         // Safe to unwrap, && will short-circuit.
         while !self.at_end() && self.peek().unwrap().is_numeric() {
-            value.push(self.advance().unwrap()); // Safe to unwrap since not end of input.
+            self.advance();
         }
 
-        let len = value.len();
-        self.create_token(TokenKind::Integer(value), len)
-    }
+        let value = &self.source[start..self.position];
+
+        // A leading zero is reserved for radix prefixes such as `0o`, rather
+        // than silently being ignored.
+        if value.len() > 1 && value.starts_with('0') {
+            return self.create_token(
+                TokenKind::Error(format!(
+                    "Integer literal `{value}` has a leading zero. Remove it, or use `0o` for an octal literal"
+                )),
+                value.len(),
+            );
+        }
 
-    fn lex_identifier(&mut self, first_char: char) -> Spanned<TokenKind> {
-        let mut value = String::from(first_char);
+        // A suffix naming the integer's type, such as `u8`, is kept as part
+        // of the token for the parser to check.
+        if self.peek().is_some_and(|c| UnicodeXID::is_xid_start(*c)) {
+            while !self.at_end() && UnicodeXID::is_xid_continue(*self.peek().unwrap()) {
+                self.advance();
+            }
+        }
 
+        let literal = &self.source[start..self.position];
+        if !is_suffix_shaped(&literal[value.len()..]) {
+            return self.create_token(
+                TokenKind::Error(format!("Invalid number literal `{literal}`")),
+                literal.len(),
+            );
+        }
+        self.create_token(TokenKind::Integer(Cow::Borrowed(literal)), literal.len())
+    }
+
+    /// Lexes an identifier or keyword without allocating. Identifiers
+    /// borrow their name from the source, and keywords hold nothing at all.
+    /// `tests/keyword_alloc.rs` checks that this holds.
+    fn lex_identifier(&mut self, start: usize) -> Spanned<TokenKind<'a>> {
         // Safe to unwrap, && will short-circuit.
         while !self.at_end() && UnicodeXID::is_xid_continue(*self.peek().unwrap()) {
-            value.push(self.advance().unwrap())
+            self.advance();
         }
 
-        let tt = get_keyword(&value);
-        self.create_token(tt, value.len())
+        let value = &self.source[start..self.position];
+        self.create_token(get_keyword(value), value.len())
     }
 
-    pub fn next_token(&mut self) -> Spanned<TokenKind> {
-        if let Some(c) = self.advance() {
-            return match c {
-                // Punctuation
-                '(' => self.create_token(TokenKind::OpenParen, 1),
-                ')' => self.create_token(TokenKind::CloseParen, 1),
-                '[' => self.create_token(TokenKind::OpenBracket, 1),
-                ']' => self.create_token(TokenKind::CloseBracket, 1),
-                ',' => self.create_token(TokenKind::Comma, 1),
-                ':' => self.create_token(TokenKind::Colon, 1),
-
-                // Operators
-                '=' => {
-                    if self.consume('=') {
-                        self.create_token(TokenKind::EqualEqual, 2)
-                    } else {
-                        self.create_token(TokenKind::Equal, 1)
-                    }
-                }
-                '!' => {
-                    if self.consume('=') {
-                        self.create_token(TokenKind::BangEqual, 2)
-                    } else {
-                        self.create_token(TokenKind::Bang, 1)
-                    }
-                }
-                '>' => {
-                    if self.consume('=') {
-                        self.create_token(TokenKind::GreaterEqual, 2)
-                    } else {
-                        self.create_token(TokenKind::Greater, 1)
-                    }
-                }
-                '<' => {
-                    if self.consume('=') {
-                        self.create_token(TokenKind::LessEqual, 2)
-                    } else {
-                        self.create_token(TokenKind::Less, 1)
-                    }
+    fn lex_label(&mut self, start: usize) -> Spanned<TokenKind<'a>> {
+        match self.peek() {
+            Some(&c) if UnicodeXID::is_xid_start(c) || c == '_' => {
+                self.advance();
+                while !self.at_end() && UnicodeXID::is_xid_continue(*self.peek().unwrap()) {
+                    self.advance();
                 }
 
-                '+' => self.create_token(TokenKind::Plus, 1),
-                '*' => self.create_token(TokenKind::Star, 1),
-                '/' => self.create_token(TokenKind::Slash, 1),
+                // Skip the leading quote.
+                let value = &self.source[start + 1..self.position];
+                self.create_token(TokenKind::Label(Cow::Borrowed(value)), value.len() + 1)
+            }
+            _ => self.create_token(
+                TokenKind::Error("Expected a label name after `'`".to_string()),
+                1,
+            ),
+        }
+    }
+
+    /// Lexes a `#` or `##` comment, whose first `#` has been consumed.
+    fn lex_line_comment(&mut self, start: usize) -> Spanned<TokenKind<'a>> {
+        let doc = self.peek() == Some(&'#');
+        while !self.at_end() && !is_line_terminator(*self.peek().unwrap()) {
+            self.advance();
+        }
+
+        // Skip the leading `#` or `##`.
+        let prefix = if doc { 2 } else { 1 };
+        let value = Cow::Borrowed(&self.source[start + prefix..self.position]);
+        let kind = if doc {
+            TokenKind::DocComment(value)
+        } else {
+            TokenKind::Comment(value)
+        };
+        self.create_token(kind, self.position - start)
+    }
+
+    /// Lexes a `/* */` or `/** */` comment, which has to be closed.
+    fn lex_block_comment(&mut self) -> Spanned<TokenKind<'a>> {
+        let start = self.position;
+        let rest = &self.source[start..];
+
+        let Some(len) = block_comment_len(rest) else {
+            while self.advance().is_some() {}
+            return self.create_token(
+                TokenKind::Error("Unterminated block comment".to_string()),
+                self.position - start,
+            );
+        };
+        while self.position < start + len {
+            self.advance();
+        }
+
+        let kind = if is_doc_block(rest) {
+            TokenKind::DocComment(Cow::Borrowed(&rest[3..len - 2]))
+        } else {
+            TokenKind::Comment(Cow::Borrowed(&rest[2..len - 2]))
+        };
+        self.create_token(kind, len)
+    }
+
+    /// Skips whitespace, and ordinary comments unless trivia is being
+    /// preserved, recording them in `trivia` if it's being preserved. Doc
+    /// comments, and ordinary comments when trivia is being preserved, are
+    /// left for `lex_token`.
+    ///
+    /// If `same_line` is set, stops at the end of the current line.
+    fn skip_whitespace(&mut self, trivia: &mut Vec<Trivia<'a>>, same_line: bool) {
+        let comments = !self.options.preserve_trivia;
+        while let Some(piece) = self.lex_trivia(same_line, comments) {
+            if self.options.preserve_trivia {
+                trivia.push(piece);
+            }
+        }
+    }
 
-                '-' => {
-                    if self.consume('>') {
-                        self.create_token(TokenKind::Arrow, 2)
-                    } else {
-                        self.create_token(TokenKind::Minus, 1)
-                    }
+    /// Lexes a single run of whitespace, or an ordinary comment if `comments`
+    /// is set, at the current position, if there is one. Unclosed block
+    /// comments are left for `lex_token` to report.
+    fn lex_trivia(&mut self, same_line: bool, comments: bool) -> Option<Trivia<'a>> {
+        let c = *self.peek()?;
+        let start = self.position;
+        let rest = &self.source[start..];
+
+        let kind = if comments && c == '#' && !rest.starts_with("##") {
+            while !self.at_end() && !is_line_terminator(*self.peek().unwrap()) {
+                self.advance();
+            }
+            TriviaKind::Comment
+        } else if comments && rest.starts_with("/*") && !is_doc_block(rest) {
+            let len = block_comment_len(rest)?;
+            while self.position < start + len {
+                self.advance();
+            }
+            TriviaKind::Comment
+        } else if c.is_whitespace() && !(same_line && is_line_terminator(c)) {
+            while let Some(&c) = self.peek() {
+                if !c.is_whitespace() || (same_line && is_line_terminator(c)) {
+                    break;
                 }
+                self.advance();
+            }
+            TriviaKind::Whitespace
+        } else {
+            return None;
+        };
+
+        Some(Trivia {
+            kind,
+            text: &self.source[start..self.position],
+            span: Span::from(start..self.position),
+        })
+    }
+
+    /// Returns the next token without consuming it. The token is buffered,
+    /// so the following call to `next_token` returns it.
+    ///
+    /// ```
+    /// use alpaca::lexer::Lexer;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let mut lexer = Lexer::new("let x");
+    /// let first = lexer.peek_token();
+    /// assert_eq!(lexer.peek_token(), first);
+    /// assert_eq!(lexer.next_token(), first);
+    /// assert_eq!(first.0, TokenKind::Let);
+    /// assert_eq!(lexer.next_token().0, TokenKind::Ident("x".into()));
+    /// ```
+    pub fn peek_token(&mut self) -> Spanned<TokenKind<'a>> {
+        let token = self.next_token_with_trivia();
+        self.peeked = Some(token.clone());
+        token.0
+    }
+
+    /// Returns the next token.
+    ///
+    /// Operators are lexed by maximal munch, always taking the longest one
+    /// which matches, so `...` is a single token rather than `..` followed
+    /// by `.`. A longer run of operator characters such as `<=<` is a custom
+    /// operator, unless the rest of it is prefix operators.
+    ///
+    /// ```
+    /// use alpaca::lexer::Lexer;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// /// Lexes `source`, separating each token with a space.
+    /// fn lex(source: &str) -> String {
+    ///     let mut lexer = Lexer::new(source);
+    ///     let mut tokens = Vec::new();
+    ///     loop {
+    ///         match lexer.next_token().0 {
+    ///             TokenKind::EoF => return tokens.join(" "),
+    ///             token => tokens.push(token.to_string()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let operators = [
+    ///     "(", ")", "[", "]", "{", "}", ",", ";", ".", "..", "..=", "...", ":", "::", "->", "=", "==", "!",
+    ///     "!=", ">", ">=", ">>", "<", "<=", "<<", "+", "-", "*", "**", "/", "//", "|>", "?",
+    /// ];
+    /// for operator in operators {
+    ///     assert_eq!(lex(operator), operator);
+    /// }
+    ///
+    /// let adjacent = [
+    ///     ("....", "... ."),
+    ///     ("..==", "..= ="),
+    ///     ("..=.", "..= ."),
+    ///     (":::", ":: :"),
+    ///     ("=-", "= -"),
+    ///     ("==!", "== !"),
+    ///     ("*-+", "* - +"),
+    ///     ("!!", "! !"),
+    /// ];
+    /// for (source, expected) in adjacent {
+    ///     assert_eq!(lex(source), expected, "lexing {source:?}");
+    /// }
+    ///
+    /// for custom in ["<=<", "<<=", ">>=", "===", "!==", "***", "///", "-->", "->>", "|>>", "!!="] {
+    ///     let mut lexer = Lexer::new(custom);
+    ///     assert_eq!(lexer.next_token().0, TokenKind::Operator(custom.into()));
+    ///     assert_eq!(lexer.next_token().0, TokenKind::EoF);
+    /// }
+    /// ```
+    pub fn next_token(&mut self) -> Spanned<TokenKind<'a>> {
+        self.next_token_with_trivia().0
+    }
+
+    /// Returns the next token along with its trivia. The trivia is always
+    /// empty unless `LexerOptions::preserve_trivia` is set.
+    ///
+    /// When it is set, ordinary comments are returned as tokens rather than
+    /// trivia, so they sit alongside doc comments in the token stream.
+    ///
+    /// ```
+    /// use alpaca::lexer::{Lexer, LexerOptions};
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let options = LexerOptions { preserve_trivia: true };
+    /// let mut lexer = Lexer::with_options("## hi\n# hi\n/** doc */ /* block */", options);
+    ///
+    /// assert_eq!(lexer.next_token().0, TokenKind::DocComment(" hi".into()));
+    /// assert_eq!(lexer.next_token().0, TokenKind::Comment(" hi".into()));
+    /// assert_eq!(lexer.next_token().0, TokenKind::DocComment(" doc ".into()));
+    /// assert_eq!(lexer.next_token().0, TokenKind::Comment(" block ".into()));
+    /// assert_eq!(lexer.next_token().0, TokenKind::EoF);
+    ///
+    /// // Otherwise ordinary comments are skipped.
+    /// let mut lexer = Lexer::new("# hi\n/* block */ 1");
+    /// assert_eq!(lexer.next_token().0, TokenKind::Integer("1".into()));
+    /// ```
+    pub fn next_token_with_trivia(&mut self) -> (Spanned<TokenKind<'a>>, TokenTrivia<'a>) {
+        self.peeked.take().unwrap_or_else(|| {
+            let mut trivia = TokenTrivia::default();
+
+            self.skip_whitespace(&mut trivia.leading, false);
+            let token = self.lex_token();
+            if token.0 != TokenKind::EoF {
+                self.skip_whitespace(&mut trivia.trailing, true);
+            }
+
+            (token, trivia)
+        })
+    }
+
+    /// Returns the next token without filtering anything out, so whitespace
+    /// and comments come back as tokens of their own. Together the spans of
+    /// the tokens cover the whole source, which lets tools such as
+    /// formatters reproduce it exactly.
+    ///
+    /// This reads straight from the source, so it shouldn't be mixed with
+    /// `peek_token`.
+    ///
+    /// ```
+    /// use alpaca::lexer::{Lexer, RawToken, TriviaKind};
+    /// use alpaca::span::Span;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let mut lexer = Lexer::new("a  b");
+    /// assert_eq!(
+    ///     lexer.next_raw_token(),
+    ///     (RawToken::Token(TokenKind::Ident("a".into())), Span::from(0..1))
+    /// );
+    /// assert_eq!(
+    ///     lexer.next_raw_token(),
+    ///     (RawToken::Trivia(TriviaKind::Whitespace), Span::from(1..3))
+    /// );
+    /// assert_eq!(
+    ///     lexer.next_raw_token(),
+    ///     (RawToken::Token(TokenKind::Ident("b".into())), Span::from(3..4))
+    /// );
+    /// assert_eq!(lexer.next_raw_token().0, RawToken::Token(TokenKind::EoF));
+    /// ```
+    pub fn next_raw_token(&mut self) -> Spanned<RawToken<'a>> {
+        if let Some(trivia) = self.lex_trivia(false, true) {
+            return (RawToken::Trivia(trivia.kind), trivia.span);
+        }
+
+        let (token, span) = self.lex_token();
+        (RawToken::Token(token), span)
+    }
+
+    /// Lexes the punctuation mark or operator at the current position, if
+    /// there is one. `OPERATORS` is searched longest first, so the longest
+    /// match is always taken.
+    ///
+    /// A run of operator characters which isn't a known operator is a custom
+    /// operator, unless it's only a known operator followed by prefix
+    /// operators, so `!-x` is still `!` and `-` applied to `x`.
+    ///
+    /// ```
+    /// use alpaca::lexer::tokenize;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let tokens: Vec<TokenKind> = tokenize("a <+> b").into_iter().map(|t| t.0).collect();
+    /// assert_eq!(
+    ///     tokens,
+    ///     [TokenKind::Ident("a".into()), TokenKind::Operator("<+>".into()), TokenKind::Ident("b".into())]
+    /// );
+    ///
+    /// let tokens: Vec<TokenKind> = tokenize("x=-1").into_iter().map(|t| t.0).collect();
+    /// assert_eq!(
+    ///     tokens,
+    ///     [TokenKind::Ident("x".into()), TokenKind::Equal, TokenKind::Minus, TokenKind::Integer("1".into())]
+    /// );
+    /// ```
+    fn lex_operator(&mut self) -> Option<Spanned<TokenKind<'a>>> {
+        let rest = &self.source[self.position..];
+        let run = operator_run(rest);
+
+        let (len, kind) = match OPERATORS.iter().find(|(text, _)| rest.starts_with(text)) {
+            Some((text, kind))
+                if text.len() >= run.len()
+                    || run[text.len()..]
+                        .chars()
+                        .all(|c| matches!(c, '-' | '+' | '!')) =>
+            {
+                (text.len(), kind.clone())
+            }
+            _ if run.is_empty() => return None,
+            _ => (run.len(), TokenKind::Operator(Cow::Borrowed(run))),
+        };
+
+        // Operators are all ASCII, so each byte is one character.
+        for _ in 0..len {
+            self.advance();
+        }
+        Some(self.create_token(kind, len))
+    }
+
+    /// Lexes the token at the current position.
+    ///
+    /// An unknown character is an error token spanning all of its bytes, so
+    /// the span can be used to slice the source.
+    ///
+    /// ```
+    /// use alpaca::lexer::Lexer;
+    /// use alpaca::span::Span;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let source = "€";
+    /// let (token, span) = Lexer::new(source).next_token();
+    /// assert!(matches!(token, TokenKind::Error(_)));
+    /// assert_eq!(span, Span::from(0..3));
+    /// assert_eq!(&source[span.start..span.end], "€");
+    /// ```
+    fn lex_token(&mut self) -> Spanned<TokenKind<'a>> {
+        if self.source[self.position..].starts_with("/*") {
+            return self.lex_block_comment();
+        }
+        if let Some(token) = self.lex_operator() {
+            return token;
+        }
 
+        let start = self.position;
+        if let Some(c) = self.advance() {
+            return match c {
                 // Literals
-                '"' => self.lex_string(),
-                c if c.is_numeric() => self.lex_number(c),
-                c if UnicodeXID::is_xid_start(c) || c == '_' => self.lex_identifier(c),
-
-                // Whitespace
-                '\n' => {
-                    self.line += 1;
-                    self.column = 0;
-                    self.next_token()
-                }
-                c if c.is_whitespace() => self.next_token(),
+                '"' => self.lex_string(start),
+                c if c.is_numeric() => self.lex_number(start),
+                c if UnicodeXID::is_xid_start(c) || c == '_' => self.lex_identifier(start),
+                '\'' => self.lex_label(start),
+                '#' => self.lex_line_comment(start),
 
-                c => self.create_token(TokenKind::Error(format!("Unknown character {c}")), 1),
+                c => self.create_token(
+                    TokenKind::Error(format!("Unknown character {c}")),
+                    c.len_utf8(),
+                ),
             };
         }
 
         self.create_token(TokenKind::EoF, 0)
     }
 }
+
+/// The run of characters custom operators are made of at the start of
+/// `source`, stopping before a block comment.
+fn operator_run(source: &str) -> &str {
+    let len = source
+        .char_indices()
+        .find(|&(i, c)| !"+-*/<>=!&|^".contains(c) || source[i..].starts_with("/*"))
+        .map_or(source.len(), |(i, _)| i);
+    &source[..len]
+}
+
+/// Whether `suffix` could name an integer type, being empty or an `i` or
+/// `u` followed by digits.
+fn is_suffix_shaped(suffix: &str) -> bool {
+    suffix.is_empty()
+        || (suffix.len() > 1
+            && suffix.starts_with(['i', 'u'])
+            && suffix[1..].bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Lexes the whole of `source`, returning every token before the end of
+/// the file, doc comments and errors included.
+///
+/// ```
+/// use alpaca::lexer::tokenize;
+/// use alpaca::tokens::TokenKind;
+///
+/// let tokens: Vec<TokenKind> = tokenize("x + 1").into_iter().map(|t| t.0).collect();
+/// assert_eq!(
+///     tokens,
+///     [TokenKind::Ident("x".into()), TokenKind::Plus, TokenKind::Integer("1".into())]
+/// );
+/// ```
+#[must_use]
+pub fn tokenize(source: &str) -> Vec<Spanned<TokenKind<'_>>> {
+    let mut lexer = Lexer::new(source);
+    let mut tokens = Vec::new();
+
+    loop {
+        match lexer.next_token() {
+            (TokenKind::EoF, _) => return tokens,
+            token => tokens.push(token),
+        }
+    }
+}
+
+/// Dumps the tokens of `source` one per line as their line and column, kind
+/// and text, for comparing against a snapshot in tests.
+///
+/// ```
+/// use alpaca::lexer::debug_tokens;
+///
+/// assert_eq!(
+///     debug_tokens("let x = 1"),
+///     "1:1 Let \"let\"\n\
+///      1:5 Ident \"x\"\n\
+///      1:7 Equal \"=\"\n\
+///      1:9 Integer \"1\""
+/// );
+/// ```
+#[must_use]
+pub fn debug_tokens(source: &str) -> String {
+    let lines: Vec<String> = tokenize(source)
+        .into_iter()
+        .map(|(token, span)| {
+            let (line, column) = line_col(source, span.start);
+            // The kind is the variant's name, without any value it holds,
+            // since that's already shown by the text.
+            let debug = format!("{token:?}");
+            let kind = debug.split('(').next().unwrap_or(&debug);
+            let text = &source[span.start..span.end];
+            format!("{line}:{column} {kind} {text:?}")
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// Checks whether `source` could be a complete program, for a REPL to decide
+/// whether to read another line of input before parsing it.
+///
+/// Source is incomplete if a `(`, `[`, `{` or `do` hasn't been closed, or it
+/// ends inside a string or block comment. Nothing else is checked, so
+/// complete source can still fail to parse. Mismatched delimiters can't be
+/// fixed by reading more, so they count as complete, leaving the parser to
+/// report them.
+///
+/// ```
+/// use alpaca::lexer::is_complete;
+///
+/// assert!(!is_complete("do 1"));
+/// assert!(is_complete("do 1 end"));
+/// assert!(!is_complete("print(\"hi"));
+/// assert!(!is_complete("[1, (2"));
+/// assert!(is_complete("(1]"));
+/// ```
+#[must_use]
+pub fn is_complete(source: &str) -> bool {
+    let mut open = Vec::new();
+
+    for (token, span) in tokenize(source) {
+        let closer = match token {
+            TokenKind::OpenParen => TokenKind::CloseParen,
+            TokenKind::OpenBracket => TokenKind::CloseBracket,
+            TokenKind::OpenBrace => TokenKind::CloseBrace,
+            TokenKind::Do => TokenKind::End,
+            TokenKind::CloseParen
+            | TokenKind::CloseBracket
+            | TokenKind::CloseBrace
+            | TokenKind::End => {
+                if open.pop() != Some(token) {
+                    return true;
+                }
+                continue;
+            }
+            // An unterminated string or block comment runs to the end of
+            // the source.
+            TokenKind::Error(_) if span.end == source.len() => {
+                let text = &source[span.start..];
+                if text.starts_with('"') || text.starts_with("/*") {
+                    return false;
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        open.push(closer);
+    }
+
+    open.is_empty()
+}
@@ -1,6 +1,12 @@
 #![warn(clippy::pedantic, clippy::nursery)]
+// The lexer only needs `alloc`, so it can be built without `std` for
+// embedded use. The parser still needs `std`, and is only built with it.
+#![cfg_attr(not(feature = "std"), no_std)]
 
-mod parser;
-mod lexer;
-mod span;
-mod tokens;
+extern crate alloc;
+
+pub mod lexer;
+#[cfg(feature = "std")]
+pub mod parser;
+pub mod span;
+pub mod tokens;
@@ -0,0 +1,249 @@
+use crate::parser::ast::Annotation;
+use crate::parser::{ErrorKind, Parser, ParserError};
+use crate::span::{Span, Spanned};
+use crate::tokens::TokenKind;
+
+type AnnotationResult = Result<Spanned<Annotation>, ParserError>;
+
+impl<'a> Parser<'a> {
+    /// Parses a type annotation.
+    ///
+    /// `Int`, `(Int, String)`, `(x: Int, y: Int)`, `[Int]`,
+    /// `(Int, Int) -> Bool`, `Int?`, `Map<String, Int>`
+    ///
+    /// ```
+    /// use alpaca::parser::ast::Annotation;
+    /// use alpaca::parser::Parser;
+    ///
+    /// let int = || Box::new(Annotation::Single("Int".to_string()));
+    /// let (annotation, _) = Parser::new("Int?", "example").parse_annotation().unwrap();
+    /// assert_eq!(annotation, Annotation::Optional(int()));
+    ///
+    /// // Naming the elements of a tuple makes it a different type.
+    /// let (annotation, _) = Parser::new("(Int, Int)", "example").parse_annotation().unwrap();
+    /// assert_eq!(annotation, Annotation::Tuple(vec![*int(), *int()]));
+    /// let (annotation, _) = Parser::new("(x: Int, y: Int)", "example").parse_annotation().unwrap();
+    /// assert_eq!(
+    ///     annotation,
+    ///     Annotation::NamedTuple(vec![("x".to_string(), *int()), ("y".to_string(), *int())])
+    /// );
+    ///
+    /// let (annotation, _) = Parser::new("Int??", "example").parse_annotation().unwrap();
+    /// assert_eq!(annotation, Annotation::Optional(Box::new(Annotation::Optional(int()))));
+    ///
+    /// let (annotation, _) = Parser::new("[String?]", "example").parse_annotation().unwrap();
+    /// assert_eq!(
+    ///     annotation,
+    ///     Annotation::Array(vec![Annotation::Optional(Box::new(Annotation::Single(
+    ///         "String".to_string()
+    ///     )))])
+    /// );
+    ///
+    /// // The closing `>>` is split in two, rather than read as a shift.
+    /// for source in ["List<Int>", "Map<String, Int>", "List<List<Int>>"] {
+    ///     let (annotation, _) = Parser::new(source, "example").parse_annotation().unwrap();
+    ///     assert_eq!(annotation.to_string(), source);
+    /// }
+    /// let (annotation, _) = Parser::new("List<List<Int>>", "example").parse_annotation().unwrap();
+    /// assert_eq!(
+    ///     annotation,
+    ///     Annotation::Generic {
+    ///         name: "List".to_string(),
+    ///         args: vec![Annotation::Generic {
+    ///             name: "List".to_string(),
+    ///             args: vec![*int()],
+    ///         }],
+    ///     }
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParserError` if the tokens don't form a valid annotation.
+    pub fn parse_annotation(&mut self) -> AnnotationResult {
+        let mut annotation = self.parse_annotation_without_suffix()?;
+
+        // Each trailing `?` makes the type before it optional.
+        while self.peek()?.0 == TokenKind::Question {
+            self.advance()?;
+            let span = Span::from(annotation.1.start..self.current_token_span.end);
+            annotation = (Annotation::Optional(Box::new(annotation.0)), span);
+        }
+
+        Ok(annotation)
+    }
+
+    fn parse_annotation_without_suffix(&mut self) -> AnnotationResult {
+        match self.advance()? {
+            (TokenKind::Ident(name), span) => {
+                if self.peek()?.0 != TokenKind::Less {
+                    return Ok((Annotation::Single(name.into_owned()), span));
+                }
+
+                self.advance()?;
+                let args = self.parse_annotation_list(&TokenKind::Greater)?;
+                let span = Span::from(span.start..self.current_token_span.end);
+                if args.is_empty() {
+                    return Err(ParserError::new(
+                        ErrorKind::Other(
+                            "Generic type without any type arguments.".to_string(),
+                            span,
+                        ),
+                        Some(format!("Give `{name}` a type, such as `{name}<Int>`.")),
+                    ));
+                }
+
+                Ok((
+                    Annotation::Generic {
+                        name: name.into_owned(),
+                        args,
+                    },
+                    span,
+                ))
+            }
+            (TokenKind::OpenParen, span) => {
+                let elements = self.parse_tuple_annotation_elements()?;
+                let named = elements.iter().filter(|(name, _)| name.is_some()).count();
+                if named > 0 {
+                    return self.named_tuple_annotation(elements, named, span);
+                }
+                let types = elements.into_iter().map(|(_, t)| t).collect();
+
+                // A parenthesized list followed by an arrow is a function type.
+                if self.peek()?.0 == TokenKind::Arrow {
+                    self.advance()?;
+                    let ret_type = self.parse_annotation()?;
+                    let span = Span::from(span.start..ret_type.1.end);
+
+                    return Ok((
+                        Annotation::Function {
+                            arg_types: types,
+                            ret_type: Box::new(ret_type.0),
+                        },
+                        span,
+                    ));
+                }
+
+                let span = Span::from(span.start..self.current_token_span.end);
+                Ok((Annotation::Tuple(types), span))
+            }
+            (TokenKind::OpenBracket, span) => {
+                let types = self.parse_annotation_list(&TokenKind::CloseBracket)?;
+                let span = Span::from(span.start..self.current_token_span.end);
+                Ok((Annotation::Array(types), span))
+            }
+            (token, span) => Err(ParserError::new(
+                ErrorKind::unexpected(token, span),
+                Some("Expected a type.".to_string()),
+            )),
+        }
+    }
+
+    /// Parses the elements of a parenthesized list of types, each of which
+    /// may be named, up to and including the closing parenthesis.
+    fn parse_tuple_annotation_elements(
+        &mut self,
+    ) -> Result<Vec<(Option<String>, Annotation)>, ParserError> {
+        let mut elements = Vec::new();
+
+        while self.peek()?.0 != TokenKind::CloseParen {
+            // A name looks like a type until the `:` after it.
+            let element = match self.parse_annotation()?.0 {
+                Annotation::Single(name) if self.peek()?.0 == TokenKind::Colon => {
+                    self.advance()?;
+                    (Some(name), self.parse_annotation()?.0)
+                }
+                annotation => (None, annotation),
+            };
+            elements.push(element);
+
+            // Consume a comma if we haven't reached the end of the list.
+            if self.peek()?.0 != TokenKind::CloseParen {
+                self.consume(&TokenKind::Comma)
+                    .map_err(|e| e.with_help("Did you forget a comma?".to_string()))?;
+            }
+        }
+
+        self.consume(&TokenKind::CloseParen)?;
+        Ok(elements)
+    }
+
+    /// Builds a named tuple type from parenthesized elements, of which
+    /// `named` have names.
+    fn named_tuple_annotation(
+        &mut self,
+        elements: Vec<(Option<String>, Annotation)>,
+        named: usize,
+        start: Span,
+    ) -> AnnotationResult {
+        let span = Span::from(start.start..self.current_token_span.end);
+        if named < elements.len() {
+            return Err(ParserError::new(
+                ErrorKind::Other(
+                    "Tuple type with both named and unnamed elements.".to_string(),
+                    span,
+                ),
+                Some("Either name every element, or none of them.".to_string()),
+            ));
+        }
+        if let (TokenKind::Arrow, arrow) = self.peek()? {
+            return Err(ParserError::new(
+                ErrorKind::Other("Function type with named arguments.".to_string(), *arrow),
+                Some("Remove the names, leaving just the types.".to_string()),
+            ));
+        }
+
+        let fields = elements
+            .into_iter()
+            .filter_map(|(name, annotation)| Some((name?, annotation)))
+            .collect();
+        Ok((Annotation::NamedTuple(fields), span))
+    }
+
+    fn parse_annotation_list(
+        &mut self,
+        closing: &TokenKind<'a>,
+    ) -> Result<Vec<Annotation>, ParserError> {
+        let mut types = Vec::new();
+
+        while !self.at_closing(closing)? {
+            types.push(self.parse_annotation()?.0);
+
+            // Consume a comma if we haven't reached the end of the list.
+            if !self.at_closing(closing)? {
+                self.consume(&TokenKind::Comma)
+                    .map_err(|e| e.with_help("Did you forget a comma?".to_string()))?;
+            }
+        }
+
+        self.consume_closing(closing)?;
+        Ok(types)
+    }
+
+    /// Checks whether the next token closes a list. A list closed by `>` can
+    /// also be closed by `>>` or `>=`, which are split by `consume_closing`.
+    fn at_closing(&mut self, closing: &TokenKind<'a>) -> Result<bool, ParserError> {
+        let token = &self.peek()?.0;
+        Ok(token == closing
+            || (*closing == TokenKind::Greater
+                && matches!(token, TokenKind::GreaterGreater | TokenKind::GreaterEqual)))
+    }
+
+    /// Consumes the token closing a list.
+    ///
+    /// The lexer reads the end of `List<List<Int>>` as a single `>>`, so
+    /// when a `>` is expected, the first character of a `>>` or `>=` is
+    /// consumed on its own and the rest is left to be read next.
+    fn consume_closing(&mut self, closing: &TokenKind<'a>) -> Result<(), ParserError> {
+        let rest = match self.peek()?.0 {
+            TokenKind::GreaterGreater if *closing == TokenKind::Greater => TokenKind::Greater,
+            TokenKind::GreaterEqual if *closing == TokenKind::Greater => TokenKind::Equal,
+            _ => return self.consume(closing),
+        };
+
+        let (_, span) = self.advance()?;
+        self.current_token_span = Span::from(span.start..span.start + 1);
+        self.peeked = Some((rest, Span::from(span.start + 1..span.end)));
+        Ok(())
+    }
+}
@@ -1,50 +1,332 @@
 //! The Abstract Syntax Tree is a tree-like representation of
 //! Alpaca's syntax.
 
+// Node types are spelled out rather than written as `Self`, which reads
+// better in recursive definitions.
+#![allow(clippy::use_self)]
+
+use std::fmt;
+
 use crate::{span::Spanned, tokens::TokenKind};
 
 /// Kinds of literals
-#[derive(Debug, PartialEq)]
+///
+/// The span of a literal covers its whole token, including the quotes
+/// around a string.
+///
+/// ```
+/// use alpaca::parser::ast::{Expr, LiteralKind};
+/// use alpaca::parser::Parser;
+/// use alpaca::span::Span;
+///
+/// let (expr, span) = Parser::new("x = \"hi\"", "example").parse_expression(1).unwrap();
+/// let Expr::Assignment { value, .. } = expr else { panic!() };
+/// assert_eq!(value.0, Expr::Literal(LiteralKind::String("hi".to_string())));
+/// assert_eq!(value.1, Span::from(4..8));
+/// assert_eq!(span, Span::from(0..8));
+/// ```
+#[derive(Debug, PartialEq, Eq)]
 pub enum LiteralKind {
-    /// Integer literal (`10`)
-    Int(i64),
+    /// Integer literal (`10`, `255u8`), with its type if it has a suffix
+    Int(i64, Option<IntType>),
     /// Boolean literal (`true`, `false`)
     Bool(bool),
     /// String literal (`"foo"`)
     String(String),
 }
 
+/// The integer types a literal can be given with a suffix, such as the `u8`
+/// in `255u8`. The parser rejects literals outside the range of their type.
+///
+/// ```
+/// use alpaca::parser::ast::{Expr, IntType, LiteralKind};
+/// use alpaca::parser::Parser;
+///
+/// let (expr, _) = Parser::new("255u8", "example").parse_expression(1).unwrap();
+/// assert_eq!(expr, Expr::Literal(LiteralKind::Int(255, Some(IntType::U8))));
+///
+/// let error = Parser::new("256u8", "example").parse_expression(1).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "error: Integer literal `256u8` is out of range for `u8`.\n  \
+///      help: `u8` holds values from 0 to 255."
+/// );
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IntType {
+    I8,
+    I16,
+    I32,
+    #[default]
+    I64,
+    U8,
+    U16,
+    U32,
+}
+
+impl IntType {
+    /// Every integer type, in the order their suffixes are listed in errors.
+    pub const ALL: [IntType; 7] = [
+        IntType::I8,
+        IntType::I16,
+        IntType::I32,
+        IntType::I64,
+        IntType::U8,
+        IntType::U16,
+        IntType::U32,
+    ];
+
+    /// The suffix naming this type.
+    #[must_use]
+    pub const fn suffix(self) -> &'static str {
+        match self {
+            IntType::I8 => "i8",
+            IntType::I16 => "i16",
+            IntType::I32 => "i32",
+            IntType::I64 => "i64",
+            IntType::U8 => "u8",
+            IntType::U16 => "u16",
+            IntType::U32 => "u32",
+        }
+    }
+
+    /// The type named by `suffix`, if there is one.
+    #[must_use]
+    pub fn from_suffix(suffix: &str) -> Option<IntType> {
+        IntType::ALL.into_iter().find(|t| t.suffix() == suffix)
+    }
+
+    /// The smallest and largest values of this type.
+    #[must_use]
+    pub const fn bounds(self) -> (i64, i64) {
+        match self {
+            IntType::I8 => (i8::MIN as i64, i8::MAX as i64),
+            IntType::I16 => (i16::MIN as i64, i16::MAX as i64),
+            IntType::I32 => (i32::MIN as i64, i32::MAX as i64),
+            IntType::I64 => (i64::MIN, i64::MAX),
+            IntType::U8 => (0, u8::MAX as i64),
+            IntType::U16 => (0, u16::MAX as i64),
+            IntType::U32 => (0, u32::MAX as i64),
+        }
+    }
+}
+
 /// Type annotations.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum Annotation {
     Single(String),
     Tuple(Vec<Annotation>),
+    /// A tuple whose elements are named, `(x: Int, y: Int)`
+    NamedTuple(Vec<(String, Annotation)>),
     Array(Vec<Annotation>),
     Function {
         arg_types: Vec<Annotation>,
         ret_type: Box<Annotation>,
     },
+    Optional(Box<Annotation>),
+    Generic {
+        name: String,
+        args: Vec<Annotation>,
+    },
+}
+
+/// Displays an annotation as it's written in the source.
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |types: &[Annotation]| {
+            types
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        match self {
+            Annotation::Single(name) => f.write_str(name),
+            Annotation::Tuple(types) => write!(f, "({})", join(types)),
+            Annotation::NamedTuple(fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(name, annotation)| format!("{name}: {annotation}"))
+                    .collect();
+                write!(f, "({})", fields.join(", "))
+            }
+            Annotation::Array(types) => write!(f, "[{}]", join(types)),
+            Annotation::Function {
+                arg_types,
+                ret_type,
+            } => write!(f, "({}) -> {ret_type}", join(arg_types)),
+            Annotation::Optional(annotation) => write!(f, "{annotation}?"),
+            Annotation::Generic { name, args } => write!(f, "{name}<{}>", join(args)),
+        }
+    }
+}
+
+/// Patterns, which a `match` compares its value against.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Pattern {
+    /// Matches anything (`_`)
+    Wildcard,
+    /// Matches anything, binding it to a name (`n`)
+    Ident(String),
+    /// Matches an equal literal (`10`, `"Hi"`)
+    Literal(LiteralKind),
+    /// Matches each item of a tuple (`(a, _)`)
+    Tuple(Vec<Spanned<Pattern>>),
+}
+
+/// A single arm of a `match`.
+///
+/// `<pattern> when <expr> -> <expr>`, where the guard is optional
+#[derive(Debug, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Spanned<Pattern>,
+    pub guard: Option<Spanned<Expr>>,
+    pub body: Spanned<Expr>,
+}
+
+/// A function parameter
+///
+/// `...mut ref <name>: <annotation> = <default>`, where everything but the
+/// name is optional
+///
+/// Each parameter keeps its own annotation, so an untyped parameter is just
+/// one whose `annotation` is `None`.
+///
+/// ```
+/// use alpaca::parser::ast::{Annotation, Statement};
+/// use alpaca::parser::Parser;
+///
+/// let (statement, _) = Parser::new("fun f(x: Int, y) do x end", "example")
+///     .parse_statement()
+///     .unwrap();
+/// let Statement::Function { params, .. } = statement else {
+///     unreachable!()
+/// };
+///
+/// assert_eq!(params.len(), 2);
+/// assert_eq!(params[0].name.0, "x");
+/// assert_eq!(params[0].annotation.as_ref().unwrap().0, Annotation::Single("Int".to_string()));
+/// assert_eq!(params[1].name.0, "y");
+/// assert_eq!(params[1].annotation, None);
+/// ```
+///
+/// `mut` and `ref` are written before the name, and are independent of the
+/// annotation.
+///
+/// ```
+/// use alpaca::parser::ast::{Annotation, Statement};
+/// use alpaca::parser::Parser;
+///
+/// let (statement, _) = Parser::new("fun f(mut a, ref b: Int, c) do a end", "example")
+///     .parse_statement()
+///     .unwrap();
+/// let Statement::Function { params, .. } = statement else {
+///     unreachable!()
+/// };
+///
+/// let summary: Vec<_> = params
+///     .iter()
+///     .map(|p| (p.name.0.as_str(), p.mutable, p.by_ref, p.annotation.as_ref().map(|a| &a.0)))
+///     .collect();
+/// let int = Annotation::Single("Int".to_string());
+/// assert_eq!(
+///     summary,
+///     [("a", true, false, None), ("b", false, true, Some(&int)), ("c", false, false, None)]
+/// );
+/// ```
+///
+/// Only the trailing parameters can have default values, so every parameter
+/// after one with a default needs one too.
+///
+/// ```
+/// use alpaca::parser::ast::{Expr, LiteralKind, Statement};
+/// use alpaca::parser::Parser;
+///
+/// let source = r#"fun greet(name, greeting = "hello") do greeting end"#;
+/// let (statement, _) = Parser::new(source, "example").parse_statement().unwrap();
+/// let Statement::Function { params, .. } = statement else {
+///     unreachable!()
+/// };
+///
+/// assert_eq!(params[0].default, None);
+/// assert_eq!(
+///     params[1].default.as_ref().unwrap().0,
+///     Expr::Literal(LiteralKind::String("hello".to_string()))
+/// );
+///
+/// let source = r#"fun greet(greeting = "hello", name) do greeting end"#;
+/// assert!(Parser::new(source, "example").parse_statement().is_err());
+/// ```
+///
+/// A variadic parameter (`...args`) collects the rest of the arguments, so
+/// only the final parameter can be one.
+///
+/// ```
+/// use alpaca::parser::ast::Statement;
+/// use alpaca::parser::Parser;
+///
+/// let source = "fun log(level, ...args) do level end";
+/// let (statement, _) = Parser::new(source, "example").parse_statement().unwrap();
+/// let Statement::Function { params, .. } = statement else {
+///     unreachable!()
+/// };
+///
+/// assert!(!params[0].variadic);
+/// assert!(params[1].variadic);
+///
+/// let source = "fun log(...args, level) do level end";
+/// assert!(Parser::new(source, "example").parse_statement().is_err());
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Param {
+    pub name: Spanned<String>,
+    pub mutable: bool,
+    pub by_ref: bool,
+    pub annotation: Option<Spanned<Annotation>>,
+    pub default: Option<Spanned<Expr>>,
+    pub variadic: bool,
 }
 
 /// An expression is an item that evaluates to some value.
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum Expr {
     /// Literals (`10`, `"Hi"`)
     Literal(LiteralKind),
     /// Identifiers (`hello`, `foo`, `bar`)
     Ident(String),
+    /// A path to an item in a module (`std::io::print`)
+    Path(Vec<String>),
+    /// An expression in parentheses (`(1 + 2)`), only produced when
+    /// `ParserOptions::preserve_grouping` is set
+    Grouping(Box<Spanned<Expr>>),
     /// Tuples (`(1, 2, 3)`)
     Tuple(Vec<Spanned<Expr>>),
     /// Arrays (`[1, 2, 3]`)
     Array(Vec<Spanned<Expr>>),
-    /// An unary operation (`!foo`, `-bar`)
+    /// The elements of a collection spread into an array or tuple
+    /// (`[1, ...xs]`), only allowed as the last element
+    Spread(Box<Spanned<Expr>>),
+    /// Maps (`{ "a": 1, "b": 2 }`)
+    Map {
+        entries: Vec<(Spanned<Expr>, Spanned<Expr>)>,
+    },
+    /// An unary operation (`!foo`, `-bar`, `+baz`)
     Unary {
-        op: TokenKind,
+        op: Spanned<TokenKind<'static>>,
         rhs: Box<Spanned<Expr>>,
     },
     /// A binary operation (`5 + 5`)
     Binary {
-        op: TokenKind,
+        op: Spanned<TokenKind<'static>>,
+        lhs: Box<Spanned<Expr>>,
+        rhs: Box<Spanned<Expr>>,
+    },
+    /// A short-circuiting logical operation (`a and b`, `a or b`), where `op`
+    /// is always `And` or `Or`
+    Logical {
+        op: Spanned<TokenKind<'static>>,
         lhs: Box<Spanned<Expr>>,
         rhs: Box<Spanned<Expr>>,
     },
@@ -53,18 +335,32 @@ pub enum Expr {
         callee: Box<Spanned<Expr>>,
         args: Vec<Spanned<Expr>>,
     },
-    /// A variable assignment (`foo = 10`)
+    /// A field access (`foo.bar`)
+    Field {
+        target: Box<Spanned<Expr>>,
+        name: Spanned<String>,
+    },
+    /// An assignment to a variable, or to a tuple of them (`foo = 10`,
+    /// `(a, b) = (1, 2)`)
     Assignment {
         name: Box<Spanned<Expr>>,
         value: Box<Spanned<Expr>>,
     },
-    /// A block
+    /// A block, optionally labeled so a `break` can target it
     ///
-    /// `do <code> end`
-    Block(Vec<Spanned<Statement>>),
+    /// `do <code> end`, `'label: do <code> end`
+    ///
+    /// An expression ending the block without a `;` is kept apart from the
+    /// statements before it as the `tail`, which is the block's value.
+    Block {
+        label: Option<Spanned<String>>,
+        statements: Vec<Spanned<Statement>>,
+        tail: Option<Box<Spanned<Expr>>>,
+    },
     /// An `if` expression
     ///
-    /// `if <expr> do <code> else <code> end`
+    /// `if <expr> do <code> else <code> end`, where the `else` is optional,
+    /// or `if <expr> then <expr> else <expr>`
     If {
         condition: Box<Spanned<Expr>>,
         body: Box<Spanned<Expr>>,
@@ -78,6 +374,14 @@ pub enum Expr {
         iter: Box<Spanned<Expr>>,
         body: Box<Spanned<Expr>>,
     },
+    /// An expression with local bindings, each of which can refer to the
+    /// ones before it, like a block of `let`s
+    ///
+    /// `<expr> where <name> = <expr>, <name> = <expr>`
+    Where {
+        body: Box<Spanned<Expr>>,
+        bindings: Vec<(Spanned<Expr>, Spanned<Expr>)>,
+    },
     /// A while loop
     ///
     /// `while <expr> do <code> end`
@@ -85,34 +389,114 @@ pub enum Expr {
         expr: Box<Spanned<Expr>>,
         body: Box<Spanned<Expr>>,
     },
+    /// A match expression
+    ///
+    /// `match <expr> do <arm> <arm> ... end`
+    Match {
+        expr: Box<Spanned<Expr>>,
+        arms: Vec<MatchArm>,
+    },
+}
+
+impl Expr {
+    /// The value of a block, which is its tail: a final expression without
+    /// a `;`. `None` means the block evaluates to unit,
+    /// as does anything which isn't a block.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Expr, LiteralKind};
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (block, _) = Parser::new("do 1 end", "example").parse_expression(1).unwrap();
+    /// assert_eq!(block.block_value().unwrap().0, Expr::Literal(LiteralKind::Int(1, None)));
+    ///
+    /// let (block, _) = Parser::new("do 1; end", "example").parse_expression(1).unwrap();
+    /// assert!(block.block_value().is_none());
+    /// ```
+    #[must_use]
+    pub fn block_value(&self) -> Option<&Spanned<Expr>> {
+        match self {
+            Expr::Block { tail, .. } => tail.as_deref(),
+            _ => None,
+        }
+    }
 }
 
 /// A statement is some standalone unit of code which does something, comprised
 /// of one or more statements.
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum Statement {
     /// An expression statement
     Expression(Spanned<Expr>),
+    /// An expression statement ending in `;`, which discards its value
+    ///
+    /// `<expr>;`
+    Semi(Spanned<Expr>),
     /// A return statement
     ///
     /// `return <expr>`
     Return(Spanned<Expr>),
+    /// An assertion, with an optional message
+    ///
+    /// `assert <expr>`, `assert <expr>, <expr>`
+    Assert {
+        cond: Spanned<Expr>,
+        message: Option<Spanned<Expr>>,
+    },
+    /// A `break` out of the enclosing loop, or out of the labeled block,
+    /// optionally with a value
+    ///
+    /// `break`, `break <expr>`, `break '<label> <expr>`
+    Break {
+        label: Option<Spanned<String>>,
+        value: Option<Spanned<Expr>>,
+    },
+    /// A `continue` to the next iteration of the enclosing loop
+    Continue,
     /// A `let` variable declaration
     ///
-    /// `let <name> = <expr>`
+    /// `let <name>: <annotation> = <expr>`, where the annotation is optional
     Let {
         name: Spanned<Expr>,
+        annotation: Option<Spanned<Annotation>>,
         value: Spanned<Expr>,
     },
-    /// A function declaration
+    /// A function declaration, with any doc comments written before it
     ///
-    /// `fun <name>(<args>) do <expr> end`
+    /// `pub fun <name>(<args>) -> <annotation> do <expr> end`, where `pub`
+    /// and the return annotation are optional
     Function {
         name: Spanned<Expr>,
+        docs: Option<String>,
         public: bool,
-        params: Vec<String>,
-        annotations: Vec<Spanned<Annotation>>,
+        params: Vec<Param>,
         return_annotation: Option<Spanned<Annotation>>,
         body: Spanned<Expr>,
     },
+    /// A function signature without a body, as declared in an interface
+    /// file
+    ///
+    /// `pub fun <name>(<args>) -> <annotation>`
+    Signature {
+        name: Spanned<Expr>,
+        docs: Option<String>,
+        public: bool,
+        params: Vec<Param>,
+        return_annotation: Option<Spanned<Annotation>>,
+    },
+    /// A type alias
+    ///
+    /// `type <name> = <annotation>`
+    TypeAlias {
+        name: Spanned<Expr>,
+        target: Spanned<Annotation>,
+    },
+    /// A record definition
+    ///
+    /// `type <name> = { <field>: <annotation>, ... }`
+    Record {
+        name: Spanned<Expr>,
+        fields: Vec<(Spanned<String>, Spanned<Annotation>)>,
+    },
 }
@@ -0,0 +1,129 @@
+//! Evaluates expressions made up only of literals and operators, for places
+//! which need a value at compile time, such as array sizes.
+
+use crate::parser::ast::{Expr, LiteralKind};
+use crate::tokens::TokenKind;
+
+/// The value of a constant expression.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ConstValue {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl ConstValue {
+    const fn as_float(self) -> Option<f64> {
+        match self {
+            #[allow(clippy::cast_precision_loss)]
+            Self::Int(i) => Some(i as f64),
+            Self::Float(f) => Some(f),
+            Self::Bool(_) => None,
+        }
+    }
+}
+
+/// Evaluates `expr` if it's made up only of integer and boolean literals and
+/// operators on them, returning `None` if anything else appears.
+///
+/// `/` always gives a float, while `//` divides integers rounding down. An
+/// integer operation which overflows or divides by zero isn't constant
+/// either.
+///
+/// ```
+/// use alpaca::parser::const_eval::{eval_const, ConstValue};
+/// use alpaca::parser::Parser;
+///
+/// let eval = |source| eval_const(&Parser::new(source, "example").parse_expression(1).unwrap().0);
+///
+/// assert_eq!(eval("2 * 3 + 1"), Some(ConstValue::Int(7)));
+/// assert_eq!(eval("7 / 2"), Some(ConstValue::Float(3.5)));
+/// assert_eq!(eval("-7 // 2"), Some(ConstValue::Int(-4)));
+/// assert_eq!(eval("1 < 2 and !false"), Some(ConstValue::Bool(true)));
+/// assert_eq!(eval("x + 1"), None);
+/// assert_eq!(eval("1 // 0"), None);
+/// ```
+#[must_use]
+pub fn eval_const(expr: &Expr) -> Option<ConstValue> {
+    match expr {
+        Expr::Literal(LiteralKind::Int(i, _)) => Some(ConstValue::Int(*i)),
+        Expr::Literal(LiteralKind::Bool(b)) => Some(ConstValue::Bool(*b)),
+        Expr::Grouping(inner) => eval_const(&inner.0),
+        Expr::Unary { op, rhs } => unary(&op.0, eval_const(&rhs.0)?),
+        Expr::Binary { op, lhs, rhs } => binary(&op.0, eval_const(&lhs.0)?, eval_const(&rhs.0)?),
+        Expr::Logical { op, lhs, rhs } => {
+            let (ConstValue::Bool(lhs), ConstValue::Bool(rhs)) =
+                (eval_const(&lhs.0)?, eval_const(&rhs.0)?)
+            else {
+                return None;
+            };
+            match op.0 {
+                TokenKind::And => Some(ConstValue::Bool(lhs && rhs)),
+                TokenKind::Or => Some(ConstValue::Bool(lhs || rhs)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn unary(op: &TokenKind, value: ConstValue) -> Option<ConstValue> {
+    match (op, value) {
+        (TokenKind::Bang, ConstValue::Bool(b)) => Some(ConstValue::Bool(!b)),
+        (TokenKind::Minus, ConstValue::Int(i)) => i.checked_neg().map(ConstValue::Int),
+        (TokenKind::Minus, ConstValue::Float(f)) => Some(ConstValue::Float(-f)),
+        (TokenKind::Plus, ConstValue::Int(_) | ConstValue::Float(_)) => Some(value),
+        _ => None,
+    }
+}
+
+// Constants compare exactly, like they would at runtime.
+#[allow(clippy::float_cmp)]
+fn binary(op: &TokenKind, lhs: ConstValue, rhs: ConstValue) -> Option<ConstValue> {
+    use ConstValue::{Bool, Float, Int};
+
+    match (op, lhs, rhs) {
+        (TokenKind::Plus, Int(a), Int(b)) => a.checked_add(b).map(Int),
+        (TokenKind::Minus, Int(a), Int(b)) => a.checked_sub(b).map(Int),
+        (TokenKind::Star, Int(a), Int(b)) => a.checked_mul(b).map(Int),
+        (TokenKind::SlashSlash, Int(a), Int(b)) => floor_div(a, b).map(Int),
+        (TokenKind::EqualEqual, Bool(a), Bool(b)) => Some(Bool(a == b)),
+        (TokenKind::BangEqual, Bool(a), Bool(b)) => Some(Bool(a != b)),
+        (TokenKind::EqualEqual, Int(a), Int(b)) => Some(Bool(a == b)),
+        (TokenKind::BangEqual, Int(a), Int(b)) => Some(Bool(a != b)),
+        (TokenKind::Less, Int(a), Int(b)) => Some(Bool(a < b)),
+        (TokenKind::LessEqual, Int(a), Int(b)) => Some(Bool(a <= b)),
+        (TokenKind::Greater, Int(a), Int(b)) => Some(Bool(a > b)),
+        (TokenKind::GreaterEqual, Int(a), Int(b)) => Some(Bool(a >= b)),
+        // Anything else on numbers is done on floats.
+        (op, lhs, rhs) => {
+            let (a, b) = (lhs.as_float()?, rhs.as_float()?);
+            match op {
+                TokenKind::Plus => Some(Float(a + b)),
+                TokenKind::Minus => Some(Float(a - b)),
+                TokenKind::Star => Some(Float(a * b)),
+                TokenKind::Slash => Some(Float(a / b)),
+                TokenKind::SlashSlash => Some(Float((a / b).floor())),
+                TokenKind::EqualEqual => Some(Bool(a == b)),
+                TokenKind::BangEqual => Some(Bool(a != b)),
+                TokenKind::Less => Some(Bool(a < b)),
+                TokenKind::LessEqual => Some(Bool(a <= b)),
+                TokenKind::Greater => Some(Bool(a > b)),
+                TokenKind::GreaterEqual => Some(Bool(a >= b)),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Divides `a` by `b`, rounding towards negative infinity.
+const fn floor_div(a: i64, b: i64) -> Option<i64> {
+    let Some(quotient) = a.checked_div(b) else {
+        return None;
+    };
+    if a % b != 0 && (a < 0) != (b < 0) {
+        Some(quotient - 1)
+    } else {
+        Some(quotient)
+    }
+}
@@ -0,0 +1,108 @@
+//! Renders parser errors for people to read, with the line of source each
+//! one points at.
+//!
+//! ```text
+//! error: expected `=`, found `2`
+//!  --> main.alp:1:7
+//!   |
+//! 1 | let y 2
+//!   |       ^
+//!   = help: Expected an `=` followed by a value.
+//! ```
+
+use crate::parser::{Parser, ParserError};
+use crate::span::line_col;
+
+/// Renders a single error, underlining the part of its line it points at.
+#[must_use]
+pub fn render_error(error: &ParserError, source: &str, filename: &str) -> String {
+    let span = error.kind().span();
+    let start = span.start.min(source.len());
+    let (line, column) = line_col(source, start);
+
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[start..]
+        .find('\n')
+        .map_or(source.len(), |i| start + i);
+    let text = &source[line_start..line_end];
+
+    // Spans running over several lines are only underlined up to the end of
+    // the first, and empty spans still get a single caret.
+    let end = span.end.clamp(start, line_end);
+    let carets = source[start..end].chars().count().max(1);
+
+    let gutter = " ".repeat(line.to_string().len());
+    let mut lines = vec![
+        format!("error: {}", error.kind()),
+        format!("{gutter}--> {}", span.display_location(source, filename)),
+        format!("{gutter} |"),
+        format!("{line} | {text}"),
+        format!(
+            "{gutter} | {}{}",
+            " ".repeat(column - 1),
+            "^".repeat(carets)
+        ),
+    ];
+    if let Some(help) = error.help() {
+        lines.push(format!("{gutter} = help: {help}"));
+    }
+    for note in error.notes() {
+        lines.push(format!("{gutter} = note: {note}"));
+    }
+
+    lines.join("\n")
+}
+
+/// Renders every error in the order they appear in the source, regardless
+/// of the order they were found in, so they read from top to bottom.
+///
+/// ```
+/// use alpaca::parser::diagnostics::render_errors;
+/// use alpaca::parser::Parser;
+///
+/// let source = "let y 2\nlet = 1";
+/// let mut errors = Parser::new(source, "main.alp").parse().unwrap_err();
+/// errors.reverse();
+///
+/// let rendered = render_errors(&errors, source, "main.alp");
+/// assert!(rendered.find("1 | let y 2").unwrap() < rendered.find("2 | let = 1").unwrap());
+/// ```
+#[must_use]
+pub fn render_errors(errors: &[ParserError], source: &str, filename: &str) -> String {
+    let mut errors: Vec<&ParserError> = errors.iter().collect();
+    errors.sort_by_key(|e| e.kind().span().start);
+
+    errors
+        .iter()
+        .map(|e| render_error(e, source, filename))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Parses `source`, rendering every error into one message ready to print,
+/// for a command line tool to report before exiting.
+///
+/// ```
+/// use alpaca::parser::diagnostics::check;
+///
+/// assert_eq!(check("let x = 1", "main.alp"), Ok(()));
+/// assert_eq!(
+///     check("let x 1", "main.alp").unwrap_err(),
+///     "error: expected `=`, found `1`
+///  --> main.alp:1:7
+///   |
+/// 1 | let x 1
+///   |       ^
+///   = help: Expected an `=` followed by a value."
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns the rendered errors if the source doesn't parse.
+pub fn check(source: &str, filename: &str) -> Result<(), String> {
+    Parser::new(source, filename)
+        .parse()
+        .map(|_| ())
+        .map_err(|errors| render_errors(&errors, source, filename))
+}
@@ -1,57 +1,384 @@
-use crate::parser::ast::{Annotation, Expr, Statement};
-use crate::parser::{Parser, ParserError};
-use crate::span::{Span, Spanned};
+use crate::parser::ast::{Expr, MatchArm, Statement};
+use crate::parser::{Diagnostic, ErrorKind, Parser, ParserError, Severity};
+use crate::span::{line_col, map_spanned, Span, Spanned};
 use crate::tokens::TokenKind;
 
-use super::ast::LiteralKind;
+use super::ast::{IntType, LiteralKind};
 
 type ExprResult = Result<Spanned<Expr>, ParserError>;
 
+/// Returns the precedence of the given token when used as an infix operator,
+/// or `0` if it isn't one.
+///
+/// From lowest to highest: assignment (`=`) and `where`, pipes (`|>`),
+/// `or`, `and`, equality, comparison, terms, factors, unary operators (9),
+/// exponentiation (`**`), and calls and field accesses. Calls and field
+/// accesses share a level, so `a.b.c(1)` chains left to right into a call
+/// of `a.b.c`.
+///
+/// `**` is the only operator which groups to the right, and binds tighter
+/// than unary operators, so `-a ** b` is `-(a ** b)`.
+///
+/// The full table of cases lives in `tests/precedence.rs`.
+pub(super) const fn infix_precedence(kind: &TokenKind) -> u8 {
+    match kind {
+        TokenKind::Equal | TokenKind::Where => 1,
+        TokenKind::Pipe => 2,
+        TokenKind::Or => 3,
+        TokenKind::And => 4,
+        TokenKind::EqualEqual | TokenKind::BangEqual => 5,
+        TokenKind::Greater | TokenKind::GreaterEqual | TokenKind::Less | TokenKind::LessEqual => 6,
+        TokenKind::Plus | TokenKind::Minus => 7,
+        TokenKind::Star | TokenKind::Slash | TokenKind::SlashSlash => 8,
+        TokenKind::StarStar => 10,
+        TokenKind::OpenParen | TokenKind::Dot => 11,
+        _ => 0,
+    }
+}
+
+/// Returns whether the given binary operator groups to the right, so
+/// `a ** b ** c` is `a ** (b ** c)`. Every other one groups to the left.
+pub(super) const fn is_right_associative(kind: &TokenKind) -> bool {
+    matches!(kind, TokenKind::StarStar)
+}
+
+/// Returns whether the given token can begin an expression.
+pub const fn starts_expression(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Integer(_)
+            | TokenKind::String(_)
+            | TokenKind::True
+            | TokenKind::False
+            | TokenKind::Ident(_)
+            | TokenKind::Label(_)
+            | TokenKind::OpenParen
+            | TokenKind::OpenBracket
+            | TokenKind::OpenBrace
+            | TokenKind::Minus
+            | TokenKind::Plus
+            | TokenKind::Bang
+            | TokenKind::Do
+            | TokenKind::If
+            | TokenKind::For
+            | TokenKind::While
+            | TokenKind::Match
+    )
+}
+
+/// Builds a block from its statements, taking a final expression without a
+/// `;` as its tail.
+///
+/// ```
+/// use alpaca::parser::Parser;
+/// use alpaca::parser::ast::Expr;
+///
+/// let (expr, _) = Parser::new("do let x = 1 x end", "example").parse_expression(1).unwrap();
+/// let Expr::Block { statements, tail, .. } = expr else {
+///     panic!("expected a block");
+/// };
+/// assert_eq!(statements.len(), 1);
+/// assert_eq!(tail.unwrap().0, Expr::Ident("x".to_string()));
+///
+/// let (expr, _) = Parser::new("do let x = 1 x; end", "example").parse_expression(1).unwrap();
+/// let Expr::Block { statements, tail, .. } = expr else {
+///     panic!("expected a block");
+/// };
+/// assert_eq!(statements.len(), 2);
+/// assert!(tail.is_none());
+/// ```
+fn block(label: Option<Spanned<String>>, mut statements: Vec<Spanned<Statement>>) -> Expr {
+    let tail = match statements.pop() {
+        Some((Statement::Expression(expr), _)) => Some(Box::new(expr)),
+        Some(statement) => {
+            statements.push(statement);
+            None
+        }
+        None => None,
+    };
+
+    Expr::Block {
+        label,
+        statements,
+        tail,
+    }
+}
+
 impl<'a> Parser<'a> {
-    /// Parses an expression.
+    /// Parses an expression, consuming infix operators which bind at least
+    /// as tightly as `precedence`.
+    ///
+    /// Operands are parsed with the prefix rules, so unary operators can
+    /// appear on either side of a binary one. A `-` is negation where an
+    /// operand is expected, and subtraction after a complete expression.
+    ///
+    /// ```
+    /// use alpaca::parser::sexpr::to_sexpr;
+    /// use alpaca::parser::Parser;
+    ///
+    /// for (source, expected) in [
+    ///     ("1 - -2", "(- 1 (- 2))"),
+    ///     ("a * -b", "(* a (- b))"),
+    ///     ("1 + +2", "(+ 1 (+ 2))"),
+    ///     ("!-x", "(! (- x))"),
+    ///     ("-1 - -2", "(- (- 1) (- 2))"),
+    ///     ("3 - -4", "(- 3 (- 4))"),
+    ///     ("- -5", "(- (- 5))"),
+    ///     ("7 // 2", "(// 7 2)"),
+    /// ] {
+    ///     let (expr, _) = Parser::new(source, "example").parse_expression(1).unwrap();
+    ///     assert_eq!(to_sexpr(&expr), expected);
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParserError` if the tokens don't form a valid expression.
     pub fn parse_expression(&mut self, precedence: u8) -> ExprResult {
-        let token = self.advance();
+        self.observe("expression", |parser| {
+            parser.parse_expression_unobserved(precedence)
+        })
+    }
+
+    fn parse_expression_unobserved(&mut self, precedence: u8) -> ExprResult {
+        let token = self.advance()?;
         let mut lhs = self.prefix_rule(token)?;
-        todo!()
+
+        while precedence <= infix_precedence(&self.peek()?.0) {
+            let token = self.advance()?;
+            lhs = self.infix_rule(token, lhs)?;
+        }
+
+        Ok(lhs)
     }
 
-    fn prefix_rule(&mut self, token: Spanned<TokenKind>) -> ExprResult {
+    fn prefix_rule(&mut self, token: Spanned<TokenKind<'a>>) -> ExprResult {
         match token.0 {
             TokenKind::Integer(_) | TokenKind::String(_) | TokenKind::True | TokenKind::False => {
                 self.parse_literal(token)
             }
-            TokenKind::Ident(s) => Ok((Expr::Ident(s), token.1)),
-            TokenKind::OpenParen => self.parse_grouping(),
-            TokenKind::Minus | TokenKind::Bang => self.parse_unary(token),
-            TokenKind::OpenBracket => self.parse_array(token),
-            TokenKind::Do => self.parse_block(token),
-            _ => todo!(),
+            TokenKind::Ident(s) => {
+                if self.peek()?.0 == TokenKind::ColonColon {
+                    self.parse_path((s.into_owned(), token.1))
+                } else {
+                    Ok((Expr::Ident(s.into_owned()), token.1))
+                }
+            }
+            TokenKind::OpenParen => self.parse_grouping(&token),
+            TokenKind::Minus | TokenKind::Plus | TokenKind::Bang => self.parse_unary(token),
+            TokenKind::OpenBracket => self.parse_array(&token),
+            TokenKind::OpenBrace => self.parse_map(&token),
+            TokenKind::Do => self.parse_block(&token, None),
+            TokenKind::Label(label) => self.parse_labeled_block((label.into_owned(), token.1)),
+            TokenKind::If => self.parse_if(&token),
+            TokenKind::For => self.parse_for(&token),
+            TokenKind::While => self.parse_while(&token),
+            TokenKind::Match => self.parse_match(&token),
+            TokenKind::Ellipsis => Err(ParserError::new(
+                ErrorKind::Unexpected(token.0.into_owned(), token.1),
+                Some("Expected an expression.".to_string()),
+            )
+            .with_note("`...` can only spread elements into an array or tuple.".to_string())),
+            // Annotations, maps and records consume their own colons, so one
+            // reaching here is out of place.
+            TokenKind::Colon => Err(ParserError::new(
+                ErrorKind::Unexpected(token.0.into_owned(), token.1),
+                Some("Expected an expression.".to_string()),
+            )
+            .with_note(
+                "`:` is only used before a type annotation, or between a key and its value."
+                    .to_string(),
+            )),
+            _ => Err(ParserError::new(
+                ErrorKind::unexpected(token.0, token.1),
+                Some("Expected an expression.".to_string()),
+            )),
         }
     }
 
-    fn parse_literal(&mut self, current: Spanned<TokenKind>) -> ExprResult {
-        Ok((
-            match current.0 {
-                TokenKind::Integer(i) => Expr::Literal(LiteralKind::Int(i.parse().unwrap())), // Safe to unwrap, value confirmed to be valid integer.
-                TokenKind::String(s) => Expr::Literal(LiteralKind::String(s)),
-                TokenKind::True => Expr::Literal(LiteralKind::Bool(true)),
-                TokenKind::False => Expr::Literal(LiteralKind::Bool(false)),
-                _ => unreachable!("parse_literal is only called when `current` is a literal."),
-            },
-            current.1,
-        ))
+    fn infix_rule(&mut self, token: Spanned<TokenKind<'a>>, lhs: Spanned<Expr>) -> ExprResult {
+        match token.0 {
+            TokenKind::Equal => self.parse_assignment(lhs),
+            TokenKind::OpenParen => self.parse_call(lhs),
+            TokenKind::Dot => self.parse_field(lhs),
+            TokenKind::Pipe => self.parse_pipe(&token, lhs),
+            TokenKind::Where => self.parse_where(lhs),
+            _ => self.parse_binary(token, lhs),
+        }
     }
 
-    fn parse_grouping(&mut self) -> ExprResult {
-        let expr = self.parse_expression(1)?;
+    /// Parses a literal. String literals written next to each other are
+    /// joined into one, so `"foo" "bar"` is the same as `"foobar"`.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Expr, LiteralKind};
+    /// use alpaca::parser::sexpr::to_sexpr;
+    /// use alpaca::parser::Parser;
+    /// use alpaca::span::Span;
+    ///
+    /// let (expr, span) = Parser::new("\"a\" \"b\"", "example").parse_expression(1).unwrap();
+    /// assert_eq!(expr, Expr::Literal(LiteralKind::String("ab".to_string())));
+    /// assert_eq!(span, Span::from(0..7));
+    ///
+    /// let (expr, _) = Parser::new("\"a\" + \"b\"", "example").parse_expression(1).unwrap();
+    /// assert_eq!(to_sexpr(&expr), "(+ \"a\" \"b\")");
+    /// ```
+    fn parse_literal(&mut self, current: Spanned<TokenKind<'a>>) -> ExprResult {
+        let TokenKind::String(s) = current.0 else {
+            let span = current.1;
+            return Ok((Expr::Literal(self.literal_kind(current.0, span)?), span));
+        };
 
-        // If next token is a comma, parse as a tuple
-        if self.peek().0 == TokenKind::Comma {
+        let mut value = s.into_owned();
+        while let TokenKind::String(s) = &self.peek()?.0 {
+            value.push_str(s);
+            self.advance()?;
+        }
+
+        let span = Span::from(current.1.start..self.current_token_span.end);
+        Ok((Expr::Literal(LiteralKind::String(value)), span))
+    }
+
+    pub(super) fn literal_kind(
+        &self,
+        kind: TokenKind<'a>,
+        span: Span,
+    ) -> Result<LiteralKind, ParserError> {
+        Ok(match kind {
+            TokenKind::Integer(i) => self.integer_literal(&i, span)?,
+            TokenKind::String(s) => LiteralKind::String(s.into_owned()),
+            TokenKind::True => LiteralKind::Bool(true),
+            TokenKind::False => LiteralKind::Bool(false),
+            _ => unreachable!("literal_kind is only called when `kind` is a literal."),
+        })
+    }
+
+    /// Checks an integer literal's suffix, and that its value fits in the
+    /// type the suffix names. Literals without a suffix are checked against
+    /// `ParserOptions::default_int`.
+    fn integer_literal(&self, literal: &str, span: Span) -> Result<LiteralKind, ParserError> {
+        let (digits, suffix) = literal.split_at(
+            literal
+                .find(|c: char| !c.is_numeric())
+                .unwrap_or(literal.len()),
+        );
+
+        let int_type = if suffix.is_empty() {
+            None
+        } else {
+            let suffixes: Vec<String> = IntType::ALL
+                .iter()
+                .map(|t| format!("`{}`", t.suffix()))
+                .collect();
+            Some(IntType::from_suffix(suffix).ok_or_else(|| {
+                ParserError::new(
+                    ErrorKind::Other(format!("Unknown integer suffix `{suffix}`."), span),
+                    Some(format!("Integer suffixes are {}.", suffixes.join(", "))),
+                )
+            })?)
+        };
+
+        let checked = int_type.unwrap_or(self.options.default_int);
+        let (min, max) = checked.bounds();
+        match digits.parse::<i64>() {
+            Ok(value) if (min..=max).contains(&value) => Ok(LiteralKind::Int(value, int_type)),
+            _ => Err(ParserError::new(
+                ErrorKind::Other(
+                    format!(
+                        "Integer literal `{literal}` is out of range for `{}`.",
+                        checked.suffix()
+                    ),
+                    span,
+                ),
+                Some(format!(
+                    "`{}` holds values from {min} to {max}.",
+                    checked.suffix()
+                )),
+            )),
+        }
+    }
+
+    /// Parses a qualified path such as `std::io::print`, after its first
+    /// segment.
+    ///
+    /// Paths are written with `::` rather than `.`, since without knowing
+    /// what `std` names the parser couldn't tell `std.io` apart from a
+    /// record field access. `.` always means a field.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::Expr;
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (expr, _) = Parser::new("std::io::print(x)", "example").parse_expression(1).unwrap();
+    /// let Expr::Call { callee, args } = expr else {
+    ///     panic!("expected a call");
+    /// };
+    /// assert_eq!(callee.0, Expr::Path(vec!["std".into(), "io".into(), "print".into()]));
+    /// assert_eq!(args[0].0, Expr::Ident("x".to_string()));
+    ///
+    /// let (expr, _) = Parser::new("std.io.print(x)", "example").parse_expression(1).unwrap();
+    /// let Expr::Call { callee, .. } = expr else {
+    ///     panic!("expected a call");
+    /// };
+    /// assert!(matches!(callee.0, Expr::Field { .. }));
+    /// ```
+    fn parse_path(&mut self, first: Spanned<String>) -> ExprResult {
+        let mut segments = vec![first.0];
+
+        while self.peek()?.0 == TokenKind::ColonColon {
+            self.advance()?;
+            match self.advance()? {
+                (TokenKind::Ident(name), _) => segments.push(name.into_owned()),
+                (token, span) => {
+                    return Err(ParserError::new(
+                        ErrorKind::ExpectedItem("identifier", token.into_owned(), span),
+                        Some("Expected a name after `::`.".to_string()),
+                    ))
+                }
+            }
+        }
+
+        let span = Span::from(first.1.start..self.current_token_span.end);
+        Ok((Expr::Path(segments), span))
+    }
+
+    /// Parses a parenthesized expression or a tuple. The parentheses are
+    /// dropped unless `ParserOptions::preserve_grouping` is set.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::Expr;
+    /// use alpaca::parser::{Parser, ParserOptions};
+    /// use alpaca::span::Span;
+    ///
+    /// let options = ParserOptions {
+    ///     preserve_grouping: true,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let (expr, span) = Parser::with_options("(1 + 2)", "example", options)
+    ///     .parse_expression(1)
+    ///     .unwrap();
+    /// assert!(matches!(expr, Expr::Grouping(inner) if matches!(inner.0, Expr::Binary { .. })));
+    /// assert_eq!(span, Span::from(0..7));
+    ///
+    /// let (expr, _) = Parser::new("(1 + 2)", "example").parse_expression(1).unwrap();
+    /// assert!(matches!(expr, Expr::Binary { .. }));
+    /// ```
+    fn parse_grouping(&mut self, current: &Spanned<TokenKind<'a>>) -> ExprResult {
+        let expr = self.parse_element()?;
+
+        // If next token is a comma, parse as a tuple. A spread can't be
+        // grouped, so on its own it's a tuple too.
+        if self.peek()?.0 == TokenKind::Comma || matches!(expr.0, Expr::Spread(_)) {
             return self.parse_tuple(expr);
         }
 
         self.consume(&TokenKind::CloseParen)
             .map_err(|e| e.with_help("Expeted to find a closing parenthesis.".to_string()))?;
+
+        if self.options.preserve_grouping {
+            let span = Span::from(current.1.start..self.current_token_span.end);
+            return Ok((Expr::Grouping(Box::new(expr)), span));
+        }
         Ok(expr)
     }
 
@@ -59,14 +386,18 @@ impl<'a> Parser<'a> {
         let start = first.1.start;
         let mut items = vec![first];
 
-        while self.peek().0 != TokenKind::CloseParen {
+        while self.peek()?.0 != TokenKind::CloseParen {
             // Consume a comma if we haven't reached the end of the tuple.
-            if self.peek().0 != TokenKind::CloseParen {
+            if self.peek()?.0 != TokenKind::CloseParen {
                 self.consume(&TokenKind::Comma)
                     .map_err(|e| e.with_help("Did you forget a comma?".to_string()))?;
             }
 
-            let item = self.parse_expression(1)?;
+            Self::check_spread_is_last(&items)?;
+            let index = items.len();
+            let item = self.parse_element().map_err(|e| {
+                e.with_note(format!("error in tuple element {index}, counting from 0"))
+            })?;
             items.push(item);
         }
 
@@ -76,29 +407,407 @@ impl<'a> Parser<'a> {
         Ok((Expr::Tuple(items), span))
     }
 
-    fn parse_unary(&mut self, current: Spanned<TokenKind>) -> ExprResult {
-        // 8 is the precedence level for the `!` and `-` unary operators.
-        let expr = self.parse_expression(8)?;
+    /// Parses a unary operation, keeping the span of the operator itself.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::Expr;
+    /// use alpaca::parser::Parser;
+    /// use alpaca::span::Span;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let (expr, span) = Parser::new("-5", "example").parse_expression(1).unwrap();
+    /// let Expr::Unary { op, rhs } = expr else {
+    ///     panic!("expected a unary operation");
+    /// };
+    /// assert_eq!(op, (TokenKind::Minus, Span::from(0..1)));
+    /// assert_eq!(rhs.1, Span::from(1..2));
+    /// assert_eq!(span, Span::from(0..2));
+    /// ```
+    fn parse_unary(&mut self, current: Spanned<TokenKind<'a>>) -> ExprResult {
+        // 9 is the precedence level for the `!`, `-` and `+` unary operators.
+        let expr = self.parse_expression(9)?;
         let span = Span::from(current.1.start..expr.1.end);
 
         Ok((
             Expr::Unary {
-                op: current.0,
+                op: map_spanned(current, TokenKind::into_owned),
                 rhs: Box::new(expr),
             },
             span,
         ))
     }
 
-    fn parse_array(&mut self, current: Spanned<TokenKind>) -> ExprResult {
+    /// Parses a binary operation. `and` and `or` only evaluate their right
+    /// hand side when they need to, so they become `Expr::Logical` instead.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::Expr;
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (expr, _) = Parser::new("a and b", "example").parse_expression(1).unwrap();
+    /// assert!(matches!(expr, Expr::Logical { .. }));
+    ///
+    /// let (expr, _) = Parser::new("a == b", "example").parse_expression(1).unwrap();
+    /// assert!(matches!(expr, Expr::Binary { .. }));
+    /// ```
+    ///
+    /// An error in the right hand side notes where the operator is, since the
+    /// error itself may point past it.
+    ///
+    /// ```
+    /// use alpaca::parser::Parser;
+    ///
+    /// let error = Parser::new("1 +", "example").parse_expression(1).unwrap_err();
+    /// assert!(error.notes().contains(&"after the `+` at 1:3".to_string()));
+    /// ```
+    ///
+    /// A parser made with `Parser::from_tokens` has no source, so the note
+    /// gives the operator's byte offset instead.
+    ///
+    /// ```
+    /// use alpaca::parser::Parser;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let tokens = vec![
+    ///     (TokenKind::Integer("1".into()), (0..1).into()),
+    ///     (TokenKind::Plus, (2..3).into()),
+    /// ];
+    /// let error = Parser::from_tokens(tokens, "example").parse_expression(1).unwrap_err();
+    /// assert!(error.notes().contains(&"after the `+` at byte 2".to_string()));
+    /// ```
+    fn parse_binary(&mut self, current: Spanned<TokenKind<'a>>, lhs: Spanned<Expr>) -> ExprResult {
+        // Most binary operators are left associative, so the right hand
+        // side only takes operators which bind tighter than this one. A right
+        // associative one takes operators at its own level too.
+        let precedence = infix_precedence(&current.0);
+        let rhs_precedence = if is_right_associative(&current.0) {
+            precedence
+        } else {
+            precedence + 1
+        };
+        let rhs = self.parse_expression(rhs_precedence).map_err(|e| {
+            // A parser made from tokens has no source to find the line in, so
+            // it falls back to the byte offset.
+            let position = if self.source.is_empty() {
+                format!("byte {}", current.1.start)
+            } else {
+                let (line, column) = line_col(self.source, current.1.start);
+                format!("{line}:{column}")
+            };
+            e.with_note(format!("after the `{}` at {position}", current.0))
+        })?;
+        let span = Span::from(lhs.1.start..rhs.1.end);
+
+        let op = map_spanned(current, TokenKind::into_owned);
+        if matches!(op.0, TokenKind::And | TokenKind::Or) {
+            return Ok((
+                Expr::Logical {
+                    op,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                },
+                span,
+            ));
+        }
+
+        if self.options.fold_constants {
+            if let Some(literal) = self.fold_constant(&op.0, &lhs.0, &rhs.0) {
+                return Ok((Expr::Literal(literal), span));
+            }
+        }
+
+        Ok((
+            Expr::Binary {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+            },
+            span,
+        ))
+    }
+
+    /// Evaluates an arithmetic operation on two integer literals of the same
+    /// type, for `ParserOptions::fold_constants`. Returns `None` if the
+    /// operation can't be folded, including when it overflows.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Expr, LiteralKind};
+    /// use alpaca::parser::{Parser, ParserOptions};
+    ///
+    /// let options = ParserOptions {
+    ///     fold_constants: true,
+    ///     ..ParserOptions::default()
+    /// };
+    ///
+    /// let (expr, _) = Parser::with_options("2 + 3", "example", options)
+    ///     .parse_expression(1)
+    ///     .unwrap();
+    /// assert_eq!(expr, Expr::Literal(LiteralKind::Int(5, None)));
+    ///
+    /// // `i64::MAX + 1` overflows, so it's left unfolded.
+    /// let source = format!("{} + 1", i64::MAX);
+    /// let (expr, _) = Parser::with_options(&source, "example", options)
+    ///     .parse_expression(1)
+    ///     .unwrap();
+    /// assert!(matches!(expr, Expr::Binary { .. }));
+    /// ```
+    fn fold_constant(&self, op: &TokenKind, lhs: &Expr, rhs: &Expr) -> Option<LiteralKind> {
+        let (
+            Expr::Literal(LiteralKind::Int(lhs, lhs_type)),
+            Expr::Literal(LiteralKind::Int(rhs, rhs_type)),
+        ) = (lhs, rhs)
+        else {
+            return None;
+        };
+        if lhs_type != rhs_type {
+            return None;
+        }
+
+        let value = match op {
+            TokenKind::Plus => lhs.checked_add(*rhs)?,
+            TokenKind::Minus => lhs.checked_sub(*rhs)?,
+            TokenKind::Star => lhs.checked_mul(*rhs)?,
+            _ => return None,
+        };
+        let (min, max) = lhs_type.unwrap_or(self.options.default_int).bounds();
+        (min..=max)
+            .contains(&value)
+            .then_some(LiteralKind::Int(value, *lhs_type))
+    }
+
+    /// Desugars `lhs |> rhs` into a call of `rhs`, with `lhs` as the first
+    /// argument. If `rhs` is already a call, `lhs` goes before its arguments,
+    /// so `x |> f(2)` becomes `f(x, 2)`.
+    ///
+    /// ```
+    /// use alpaca::parser::sexpr::to_sexpr;
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (expr, _) = Parser::new("x |> f", "example").parse_expression(1).unwrap();
+    /// let (call, _) = Parser::new("f(x)", "example").parse_expression(1).unwrap();
+    /// assert_eq!(to_sexpr(&expr), to_sexpr(&call));
+    ///
+    /// let (expr, _) = Parser::new("x |> f(2)", "example").parse_expression(1).unwrap();
+    /// let (call, _) = Parser::new("f(x, 2)", "example").parse_expression(1).unwrap();
+    /// assert_eq!(to_sexpr(&expr), to_sexpr(&call));
+    /// ```
+    fn parse_pipe(&mut self, current: &Spanned<TokenKind<'a>>, lhs: Spanned<Expr>) -> ExprResult {
+        let rhs = self.parse_expression(infix_precedence(&current.0) + 1)?;
+        let span = Span::from(lhs.1.start..rhs.1.end);
+
+        let (callee, args) = match rhs {
+            (Expr::Call { callee, mut args }, _) => {
+                args.insert(0, lhs);
+                (callee, args)
+            }
+            rhs => (Box::new(rhs), vec![lhs]),
+        };
+        Ok((Expr::Call { callee, args }, span))
+    }
+
+    /// Parses an assignment to a variable, or to a tuple of targets which
+    /// destructures the value.
+    ///
+    /// ```
+    /// use alpaca::parser::sexpr::to_sexpr;
+    /// use alpaca::parser::Parser;
+    /// use alpaca::span::Span;
+    ///
+    /// let (expr, _) = Parser::new("(a, b) = (1, 2)", "example").parse_expression(1).unwrap();
+    /// assert_eq!(to_sexpr(&expr), "(= (tuple a b) (tuple 1 2))");
+    ///
+    /// let error = Parser::new("(1, b) = x", "example").parse_expression(1).unwrap_err();
+    /// assert_eq!(error.kind().span(), Span::from(1..2));
+    /// ```
+    fn parse_assignment(&mut self, name: Spanned<Expr>) -> ExprResult {
+        Self::check_assignable(&name)?;
+
+        // Assignment is right associative, `a = b = c` is `a = (b = c)`.
+        let value = self.parse_expression(1)?;
+        let span = Span::from(name.1.start..value.1.end);
+
+        Ok((
+            Expr::Assignment {
+                name: Box::new(name),
+                value: Box::new(value),
+            },
+            span,
+        ))
+    }
+
+    /// Checks that `target` can be assigned to, pointing the error at the
+    /// first part of it which can't be.
+    fn check_assignable(target: &Spanned<Expr>) -> Result<(), ParserError> {
+        match &target.0 {
+            Expr::Ident(_) => Ok(()),
+            Expr::Grouping(inner) => Self::check_assignable(inner),
+            Expr::Tuple(items) => items.iter().try_for_each(Self::check_assignable),
+            _ => Err(ParserError::new(
+                ErrorKind::Other("Invalid assignment target.".to_string(), target.1),
+                Some("Only variables, or tuples of them, can be assigned to.".to_string()),
+            )),
+        }
+    }
+
+    /// Parses the bindings of a `where` clause following `body`.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::Expr;
+    /// use alpaca::parser::sexpr::to_sexpr;
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (expr, _) = Parser::new("x + y where x = 1, y = 2", "example")
+    ///     .parse_expression(1)
+    ///     .unwrap();
+    /// let Expr::Where { bindings, .. } = &expr else {
+    ///     panic!("expected a `where`");
+    /// };
+    /// assert_eq!(bindings.len(), 2);
+    /// assert_eq!(to_sexpr(&expr), "(where (+ x y) (x 1) (y 2))");
+    /// ```
+    fn parse_where(&mut self, body: Spanned<Expr>) -> ExprResult {
+        let mut bindings = Vec::new();
+
+        loop {
+            let name = self.parse_identifier("variable")?;
+            self.consume(&TokenKind::Equal).map_err(|e| {
+                e.with_help("Each binding is a name, `=`, and a value.".to_string())
+            })?;
+            // Values sit above assignment, so their `=` isn't taken as one.
+            let value = self.parse_expression(2)?;
+            bindings.push((name, value));
+
+            if self.peek()?.0 != TokenKind::Comma {
+                break;
+            }
+            self.advance()?;
+        }
+
+        let span = Span::from(body.1.start..self.current_token_span.end);
+        Ok((
+            Expr::Where {
+                body: Box::new(body),
+                bindings,
+            },
+            span,
+        ))
+    }
+
+    /// Parses the arguments of a call, after its `(`. Any expression can be
+    /// an argument, including a block, whose span ends at its `end` rather
+    /// than taking in the closing parenthesis.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::Expr;
+    /// use alpaca::parser::Parser;
+    /// use alpaca::span::Span;
+    ///
+    /// let (expr, span) = Parser::new("f(do 1 end)", "example").parse_expression(1).unwrap();
+    /// let Expr::Call { args, .. } = expr else {
+    ///     panic!("expected a call");
+    /// };
+    /// assert!(matches!(args[0].0, Expr::Block { .. }));
+    /// assert_eq!(args[0].1, Span::from(2..10));
+    /// assert_eq!(span, Span::from(0..11));
+    /// ```
+    fn parse_call(&mut self, callee: Spanned<Expr>) -> ExprResult {
+        let mut args = Vec::new();
+
+        while self.peek()?.0 != TokenKind::CloseParen {
+            let arg = self.parse_expression(1)?;
+            args.push(arg);
+
+            // Consume a comma if we haven't reached the end of the arguments.
+            if self.peek()?.0 != TokenKind::CloseParen {
+                self.consume(&TokenKind::Comma)
+                    .map_err(|e| e.with_help("Did you forget a comma?".to_string()))?;
+            }
+        }
+
+        self.consume(&TokenKind::CloseParen)
+            .map_err(|e| e.with_help("Expeted to find a closing parenthesis.".to_string()))?;
+
+        let span = Span::from(callee.1.start..self.current_token_span.end);
+        Ok((
+            Expr::Call {
+                callee: Box::new(callee),
+                args,
+            },
+            span,
+        ))
+    }
+
+    /// Parses a field access, after its `.`. Field accesses and calls
+    /// chain left to right, so a method call is a call of a field.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Expr, LiteralKind};
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (expr, _) = Parser::new("a.b.c(1)", "example").parse_expression(1).unwrap();
+    /// let Expr::Call { callee, args } = expr else {
+    ///     panic!("expected a call");
+    /// };
+    /// assert_eq!(args[0].0, Expr::Literal(LiteralKind::Int(1, None)));
+    ///
+    /// let Expr::Field { target, name } = callee.0 else {
+    ///     panic!("expected a field access");
+    /// };
+    /// assert_eq!(name.0, "c");
+    /// assert!(matches!(target.0, Expr::Field { ref name, .. } if name.0 == "b"));
+    /// ```
+    fn parse_field(&mut self, target: Spanned<Expr>) -> ExprResult {
+        let name = match self.advance()? {
+            (TokenKind::Ident(name), span) => (name.into_owned(), span),
+            (token, span) => {
+                return Err(ParserError::new(
+                    ErrorKind::ExpectedItem("field name", token.into_owned(), span),
+                    Some("Expected a field name after `.`.".to_string()),
+                ))
+            }
+        };
+
+        let span = Span::from(target.1.start..name.1.end);
+        Ok((
+            Expr::Field {
+                target: Box::new(target),
+                name,
+            },
+            span,
+        ))
+    }
+
+    /// Parses an array literal. An element which fails to parse gets a note
+    /// with its index.
+    ///
+    /// ```
+    /// use alpaca::parser::Parser;
+    ///
+    /// let error = Parser::new("[1, 2, =]", "example").parse_expression(1).unwrap_err();
+    /// assert_eq!(error.notes(), ["error in array element 2, counting from 0"]);
+    ///
+    /// let error = Parser::new("(1, [2, =])", "example").parse_expression(1).unwrap_err();
+    /// assert_eq!(
+    ///     error.notes(),
+    ///     [
+    ///         "error in array element 1, counting from 0",
+    ///         "error in tuple element 1, counting from 0",
+    ///     ]
+    /// );
+    /// ```
+    fn parse_array(&mut self, current: &Spanned<TokenKind<'a>>) -> ExprResult {
         let mut items = Vec::new();
 
-        while self.peek().0 != TokenKind::CloseBracket {
-            let item = self.parse_expression(1)?;
+        while self.peek()?.0 != TokenKind::CloseBracket {
+            Self::check_spread_is_last(&items)?;
+            let index = items.len();
+            let item = self.parse_element().map_err(|e| {
+                e.with_note(format!("error in array element {index}, counting from 0"))
+            })?;
             items.push(item);
 
             // Consume a comma if we haven't reached the end of the array.
-            if self.peek().0 != TokenKind::CloseBracket {
+            if self.peek()?.0 != TokenKind::CloseBracket {
                 self.consume(&TokenKind::Comma)
                     .map_err(|e| e.with_help("Did you forget a comma?".to_string()))?;
             }
@@ -111,16 +820,340 @@ impl<'a> Parser<'a> {
         Ok((Expr::Array(items), span))
     }
 
-    fn parse_block(&mut self, current: Spanned<TokenKind>) -> ExprResult {
-        let mut expressions = vec![];
+    /// Parses an element of an array or tuple, which can be a spread of
+    /// another collection's elements.
+    ///
+    /// ```
+    /// use alpaca::parser::sexpr::to_sexpr;
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (expr, _) = Parser::new("[1, ...xs]", "example").parse_expression(1).unwrap();
+    /// assert_eq!(to_sexpr(&expr), "(array 1 (... xs))");
+    ///
+    /// let (expr, _) = Parser::new("(a, ...rest)", "example").parse_expression(1).unwrap();
+    /// assert_eq!(to_sexpr(&expr), "(tuple a (... rest))");
+    ///
+    /// // A spread has to be the last element.
+    /// assert!(Parser::new("[...xs, 1]", "example").parse_expression(1).is_err());
+    /// ```
+    fn parse_element(&mut self) -> ExprResult {
+        if self.peek()?.0 != TokenKind::Ellipsis {
+            return self.parse_expression(1);
+        }
+
+        let start = self.advance()?.1.start;
+        let expr = self.parse_expression(1)?;
+        let span = Span::from(start..expr.1.end);
+        Ok((Expr::Spread(Box::new(expr)), span))
+    }
 
-        while !self.at_end() && self.peek().0 != TokenKind::End {
-            expressions.push(todo!());
+    /// Checks that no element follows a spread, before parsing another.
+    fn check_spread_is_last(items: &[Spanned<Expr>]) -> Result<(), ParserError> {
+        match items.last() {
+            Some((Expr::Spread(_), span)) => Err(ParserError::new(
+                ErrorKind::Other("A spread has to be the last element.".to_string(), *span),
+                Some("Move it after the other elements.".to_string()),
+            )),
+            _ => Ok(()),
         }
+    }
+
+    /// Parses a map literal, a list of `key: value` entries in braces.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Expr, LiteralKind};
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (expr, _) = Parser::new("{ \"a\": 1, \"b\": 2 }", "example").parse_expression(1).unwrap();
+    /// let Expr::Map { entries } = expr else {
+    ///     panic!("expected a map");
+    /// };
+    /// let entries: Vec<_> = entries.into_iter().map(|(k, v)| (k.0, v.0)).collect();
+    /// let string = |s: &str| Expr::Literal(LiteralKind::String(s.to_string()));
+    /// let int = |i| Expr::Literal(LiteralKind::Int(i, None));
+    /// assert_eq!(entries, [(string("a"), int(1)), (string("b"), int(2))]);
+    ///
+    /// let (expr, _) = Parser::new("{}", "example").parse_expression(1).unwrap();
+    /// assert_eq!(expr, Expr::Map { entries: vec![] });
+    /// ```
+    fn parse_map(&mut self, current: &Spanned<TokenKind<'a>>) -> ExprResult {
+        let mut entries = Vec::new();
 
+        while self.peek()?.0 != TokenKind::CloseBrace {
+            let key = self.parse_expression(1)?;
+            self.consume(&TokenKind::Colon).map_err(|e| {
+                e.with_help("Expected a `:` between the key and value.".to_string())
+            })?;
+            let value = self.parse_expression(1)?;
+            entries.push((key, value));
+
+            // Consume a comma if we haven't reached the end of the map.
+            if self.peek()?.0 != TokenKind::CloseBrace {
+                self.consume(&TokenKind::Comma)
+                    .map_err(|e| e.with_help("Did you forget a comma?".to_string()))?;
+            }
+        }
+
+        self.consume(&TokenKind::CloseBrace)
+            .map_err(|e| e.with_help("Expeted to find a closing brace.".to_string()))?;
+
+        let span = Span::from(current.1.start..self.current_token_span.end);
+        Ok((Expr::Map { entries }, span))
+    }
+
+    fn parse_block(
+        &mut self,
+        current: &Spanned<TokenKind<'a>>,
+        label: Option<Spanned<String>>,
+    ) -> ExprResult {
+        let statements = self.parse_statements_until(&[TokenKind::End])?;
         self.consume(&TokenKind::End)
             .map_err(|e| e.with_help("Did you forget an `end`?".to_string()))?;
+
+        // A labeled block's span starts at its label.
+        let start = label.as_ref().map_or(current.1.start, |l| l.1.start);
+        let span = Span::from(start..self.current_token_span.end);
+        Ok((block(label, statements), span))
+    }
+
+    /// Parses statements up until one of the given tokens, without
+    /// consuming it.
+    fn parse_statements_until(
+        &mut self,
+        terminators: &[TokenKind<'a>],
+    ) -> Result<Vec<Spanned<Statement>>, ParserError> {
+        let mut statements = vec![];
+
+        while !self.at_end() && !terminators.contains(&self.peek()?.0) {
+            statements.push(self.parse_statement()?);
+        }
+
+        self.check_reachable(&statements);
+        Ok(statements)
+    }
+
+    /// Warns about statements in a block which follow a `return`, `break` or
+    /// `continue`, since they can never run.
+    fn check_reachable(&mut self, statements: &[Spanned<Statement>]) {
+        let Some(jump) = statements.iter().position(|(statement, _)| {
+            matches!(
+                statement,
+                Statement::Return(_) | Statement::Break { .. } | Statement::Continue
+            )
+        }) else {
+            return;
+        };
+
+        let unreachable = &statements[jump + 1..];
+        if let (Some(first), Some(last)) = (unreachable.first(), unreachable.last()) {
+            self.diagnostics.push(Diagnostic {
+                severity: Severity::Warning,
+                message: "unreachable code".to_string(),
+                span: Span::from(first.1.start..last.1.end),
+                help: None,
+                notes: Vec::new(),
+                code: Some("unreachable_code"),
+            });
+        }
+    }
+
+    fn parse_labeled_block(&mut self, label: Spanned<String>) -> ExprResult {
+        self.consume(&TokenKind::Colon)
+            .map_err(|e| e.with_help("A label is followed by `:` and a block.".to_string()))?;
+        self.parse_do_block(Some(label), "Only blocks can be labeled.")
+    }
+
+    /// Parses a `do ... end` block where one is required, using `help` to
+    /// explain why if it's missing.
+    pub(super) fn parse_do_block(
+        &mut self,
+        label: Option<Spanned<String>>,
+        help: &str,
+    ) -> ExprResult {
+        let do_token = self.advance()?;
+        if do_token.0 != TokenKind::Do {
+            return Err(ParserError::new(
+                ErrorKind::Expected(vec![TokenKind::Do], do_token.0.into_owned(), do_token.1),
+                Some(help.to_string()),
+            ));
+        }
+
+        self.parse_block(&do_token, label)
+    }
+
+    /// Parses an `if`, written either with a `do ... end` body or as
+    /// `if <expr> then <expr> else <expr>`.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Expr, LiteralKind};
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (expr, _) = Parser::new("if c then 1 else 2", "example").parse_expression(1).unwrap();
+    /// let Expr::If { condition, body, else_ } = expr else {
+    ///     panic!("expected an if");
+    /// };
+    /// assert_eq!(condition.0, Expr::Ident("c".to_string()));
+    /// assert_eq!(body.0, Expr::Literal(LiteralKind::Int(1, None)));
+    /// assert_eq!(else_.unwrap().0, Expr::Literal(LiteralKind::Int(2, None)));
+    ///
+    /// assert!(Parser::new("if c then 1", "example").parse_expression(1).is_err());
+    /// ```
+    fn parse_if(&mut self, current: &Spanned<TokenKind<'a>>) -> ExprResult {
+        let condition = self.parse_expression(1)?;
+
+        // `if <expr> then <expr> else <expr>`, which needs no `end` since
+        // each branch is a single expression.
+        let (body, else_) = if self.at_soft_keyword("then") {
+            self.advance()?;
+            let body = self.parse_expression(1)?;
+            self.consume(&TokenKind::Else).map_err(|e| {
+                e.with_help("An `if` using `then` must have an `else`.".to_string())
+            })?;
+            (body, Some(self.parse_expression(1)?))
+        } else {
+            match self.advance()? {
+                (TokenKind::Do, span) => {
+                    let statements =
+                        self.parse_statements_until(&[TokenKind::Else, TokenKind::End])?;
+                    let body_span = Span::from(span.start..self.current_token_span.end);
+                    let body = (block(None, statements), body_span);
+
+                    let else_ = if self.peek()?.0 == TokenKind::Else {
+                        let start = self.advance()?.1.start;
+                        let statements = self.parse_statements_until(&[TokenKind::End])?;
+                        let span = Span::from(start..self.current_token_span.end);
+                        Some((block(None, statements), span))
+                    } else {
+                        None
+                    };
+
+                    self.consume(&TokenKind::End)
+                        .map_err(|e| e.with_help("Did you forget an `end`?".to_string()))?;
+                    (body, else_)
+                }
+                (token, span) => {
+                    return Err(ParserError::new(
+                        ErrorKind::ExpectedItem("`do` or `then`", token.into_owned(), span),
+                        Some("An `if`'s condition is followed by `do` or `then`.".to_string()),
+                    ))
+                }
+            }
+        };
+
+        let span = Span::from(current.1.start..self.current_token_span.end);
+        Ok((
+            Expr::If {
+                condition: Box::new(condition),
+                body: Box::new(body),
+                else_: Box::new(else_),
+            },
+            span,
+        ))
+    }
+
+    fn parse_for(&mut self, current: &Spanned<TokenKind<'a>>) -> ExprResult {
+        let var = self.parse_identifier("variable")?;
+        self.consume_soft_keyword("in")
+            .map_err(|e| e.with_help("A `for` loop's variable is followed by `in`.".to_string()))?;
+        let iter = self.parse_expression(1)?;
+        let body = self.parse_do_block(None, "A `for` loop's body starts with `do`.")?;
+        let span = Span::from(current.1.start..body.1.end);
+
+        Ok((
+            Expr::For {
+                var: Box::new(var),
+                iter: Box::new(iter),
+                body: Box::new(body),
+            },
+            span,
+        ))
+    }
+
+    /// Parses a `while` loop, whose body can leave it with `break`.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Expr, Statement};
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (expr, _) = Parser::new("while x do break end", "example").parse_expression(1).unwrap();
+    /// let Expr::While { body, .. } = expr else {
+    ///     panic!("expected a while loop");
+    /// };
+    /// let Expr::Block { statements, .. } = body.0 else {
+    ///     panic!("expected a block");
+    /// };
+    /// assert!(matches!(statements[..], [(Statement::Break { .. }, _)]));
+    /// ```
+    fn parse_while(&mut self, current: &Spanned<TokenKind<'a>>) -> ExprResult {
+        let expr = self.parse_expression(1)?;
+        let body = self.parse_do_block(None, "A `while` loop's body starts with `do`.")?;
+        let span = Span::from(current.1.start..body.1.end);
+
+        Ok((
+            Expr::While {
+                expr: Box::new(expr),
+                body: Box::new(body),
+            },
+            span,
+        ))
+    }
+
+    fn parse_match(&mut self, current: &Spanned<TokenKind<'a>>) -> ExprResult {
+        let expr = self.parse_expression(1)?;
+        self.consume(&TokenKind::Do)
+            .map_err(|e| e.with_help("A `match`'s arms start with `do`.".to_string()))?;
+
+        let mut arms = Vec::new();
+        while !matches!(self.peek()?.0, TokenKind::End | TokenKind::EoF) {
+            arms.push(self.parse_match_arm()?);
+        }
+        self.consume(&TokenKind::End)
+            .map_err(|e| e.with_help("Did you forget an `end`?".to_string()))?;
+
         let span = Span::from(current.1.start..self.current_token_span.end);
-        Ok((Expr::Block(expressions), span))
+        Ok((
+            Expr::Match {
+                expr: Box::new(expr),
+                arms,
+            },
+            span,
+        ))
+    }
+
+    /// Parses a single arm of a `match`, `<pattern> when <expr> -> <expr>`,
+    /// where the guard is optional.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Expr, Pattern};
+    /// use alpaca::parser::Parser;
+    ///
+    /// let source = "match n do\n    x when x > 0 -> 1\n    _ -> 0\nend";
+    /// let (expr, _) = Parser::new(source, "example").parse_expression(1).unwrap();
+    /// let Expr::Match { arms, .. } = expr else {
+    ///     panic!("expected a match");
+    /// };
+    /// assert_eq!(arms[0].pattern.0, Pattern::Ident("x".to_string()));
+    /// assert!(matches!(arms[0].guard, Some((Expr::Binary { .. }, _))));
+    /// assert_eq!(arms[1].pattern.0, Pattern::Wildcard);
+    /// assert!(arms[1].guard.is_none());
+    /// ```
+    fn parse_match_arm(&mut self) -> Result<MatchArm, ParserError> {
+        let pattern = self.parse_pattern()?;
+        let guard = if self.at_soft_keyword("when") {
+            self.advance()?;
+            Some(self.parse_expression(1)?)
+        } else {
+            None
+        };
+
+        self.consume(&TokenKind::Arrow)
+            .map_err(|e| e.with_help("Expected a `->` followed by the arm's body.".to_string()))?;
+        let body = self.parse_expression(1)?;
+
+        Ok(MatchArm {
+            pattern,
+            guard,
+            body,
+        })
     }
 }
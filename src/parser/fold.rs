@@ -0,0 +1,231 @@
+//! The `Fold` trait, for passes which rewrite the AST.
+//!
+//! A fold takes ownership of each node and returns its replacement. The
+//! default methods rebuild every node from its folded children, keeping all
+//! spans intact, so a pass only overrides the nodes it rewrites.
+
+use crate::parser::ast::{Expr, MatchArm, Param, Statement};
+use crate::span::Spanned;
+
+/// Rewrites an AST node by node.
+///
+/// Overriding methods can call the free function of the same name to fold
+/// the children of a node they don't otherwise rewrite.
+///
+/// ```
+/// use alpaca::parser::ast::{Expr, LiteralKind};
+/// use alpaca::parser::fold::{self, Fold};
+/// use alpaca::parser::Parser;
+/// use alpaca::span::Spanned;
+///
+/// /// Increments every integer literal.
+/// struct Increment;
+///
+/// impl Fold for Increment {
+///     fn fold_expr(&mut self, expr: Spanned<Expr>) -> Spanned<Expr> {
+///         match expr {
+///             (Expr::Literal(LiteralKind::Int(n, suffix)), span) => {
+///                 (Expr::Literal(LiteralKind::Int(n + 1, suffix)), span)
+///             }
+///             expr => fold::fold_expr(self, expr),
+///         }
+///     }
+/// }
+///
+/// let expr = Parser::new("[1, 2]", "example").parse_expression(1).unwrap();
+/// let expected = Parser::new("[2, 3]", "example").parse_expression(1).unwrap();
+/// assert_eq!(Increment.fold_expr(expr), expected);
+/// ```
+pub trait Fold {
+    fn fold_statement(&mut self, statement: Spanned<Statement>) -> Spanned<Statement> {
+        fold_statement(self, statement)
+    }
+
+    fn fold_expr(&mut self, expr: Spanned<Expr>) -> Spanned<Expr> {
+        fold_expr(self, expr)
+    }
+}
+
+/// Folds each of the given statements.
+pub fn fold_statements<F: Fold + ?Sized>(
+    folder: &mut F,
+    statements: Vec<Spanned<Statement>>,
+) -> Vec<Spanned<Statement>> {
+    statements
+        .into_iter()
+        .map(|s| folder.fold_statement(s))
+        .collect()
+}
+
+fn fold_exprs<F: Fold + ?Sized>(folder: &mut F, exprs: Vec<Spanned<Expr>>) -> Vec<Spanned<Expr>> {
+    exprs.into_iter().map(|e| folder.fold_expr(e)).collect()
+}
+
+/// Folds the default values of parameters.
+fn fold_params<F: Fold + ?Sized>(folder: &mut F, params: Vec<Param>) -> Vec<Param> {
+    params
+        .into_iter()
+        .map(|param| Param {
+            default: param.default.map(|d| folder.fold_expr(d)),
+            ..param
+        })
+        .collect()
+}
+
+/// Rebuilds a statement from its folded children.
+pub fn fold_statement<F: Fold + ?Sized>(
+    folder: &mut F,
+    (statement, span): Spanned<Statement>,
+) -> Spanned<Statement> {
+    let statement = match statement {
+        Statement::Expression(expr) => Statement::Expression(folder.fold_expr(expr)),
+        Statement::Semi(expr) => Statement::Semi(folder.fold_expr(expr)),
+        Statement::Return(expr) => Statement::Return(folder.fold_expr(expr)),
+        Statement::Assert { cond, message } => Statement::Assert {
+            cond: folder.fold_expr(cond),
+            message: message.map(|m| folder.fold_expr(m)),
+        },
+        Statement::Break { label, value } => Statement::Break {
+            label,
+            value: value.map(|v| folder.fold_expr(v)),
+        },
+        Statement::Continue => Statement::Continue,
+        Statement::Let {
+            name,
+            annotation,
+            value,
+        } => Statement::Let {
+            name: folder.fold_expr(name),
+            annotation,
+            value: folder.fold_expr(value),
+        },
+        Statement::Function {
+            name,
+            docs,
+            public,
+            params,
+            return_annotation,
+            body,
+        } => Statement::Function {
+            name: folder.fold_expr(name),
+            docs,
+            public,
+            params: fold_params(folder, params),
+            return_annotation,
+            body: folder.fold_expr(body),
+        },
+        Statement::Signature {
+            name,
+            docs,
+            public,
+            params,
+            return_annotation,
+        } => Statement::Signature {
+            name: folder.fold_expr(name),
+            docs,
+            public,
+            params: fold_params(folder, params),
+            return_annotation,
+        },
+        Statement::TypeAlias { name, target } => Statement::TypeAlias {
+            name: folder.fold_expr(name),
+            target,
+        },
+        Statement::Record { name, fields } => Statement::Record {
+            name: folder.fold_expr(name),
+            fields,
+        },
+    };
+
+    (statement, span)
+}
+
+/// Rebuilds an expression from its folded children.
+pub fn fold_expr<F: Fold + ?Sized>(folder: &mut F, (expr, span): Spanned<Expr>) -> Spanned<Expr> {
+    let expr = match expr {
+        Expr::Literal(_) | Expr::Ident(_) | Expr::Path(_) => expr,
+        Expr::Grouping(expr) => Expr::Grouping(Box::new(folder.fold_expr(*expr))),
+        Expr::Tuple(items) => Expr::Tuple(fold_exprs(folder, items)),
+        Expr::Array(items) => Expr::Array(fold_exprs(folder, items)),
+        Expr::Spread(expr) => Expr::Spread(Box::new(folder.fold_expr(*expr))),
+        Expr::Map { entries } => Expr::Map {
+            entries: entries
+                .into_iter()
+                .map(|(k, v)| (folder.fold_expr(k), folder.fold_expr(v)))
+                .collect(),
+        },
+        Expr::Unary { op, rhs } => Expr::Unary {
+            op,
+            rhs: Box::new(folder.fold_expr(*rhs)),
+        },
+        Expr::Logical { op, lhs, rhs } => Expr::Logical {
+            op,
+            lhs: Box::new(folder.fold_expr(*lhs)),
+            rhs: Box::new(folder.fold_expr(*rhs)),
+        },
+        Expr::Binary { op, lhs, rhs } => Expr::Binary {
+            op,
+            lhs: Box::new(folder.fold_expr(*lhs)),
+            rhs: Box::new(folder.fold_expr(*rhs)),
+        },
+        Expr::Call { callee, args } => Expr::Call {
+            callee: Box::new(folder.fold_expr(*callee)),
+            args: fold_exprs(folder, args),
+        },
+        Expr::Field { target, name } => Expr::Field {
+            target: Box::new(folder.fold_expr(*target)),
+            name,
+        },
+        Expr::Assignment { name, value } => Expr::Assignment {
+            name: Box::new(folder.fold_expr(*name)),
+            value: Box::new(folder.fold_expr(*value)),
+        },
+        Expr::Block {
+            label,
+            statements,
+            tail,
+        } => Expr::Block {
+            label,
+            statements: fold_statements(folder, statements),
+            tail: tail.map(|t| Box::new(folder.fold_expr(*t))),
+        },
+        Expr::If {
+            condition,
+            body,
+            else_,
+        } => Expr::If {
+            condition: Box::new(folder.fold_expr(*condition)),
+            body: Box::new(folder.fold_expr(*body)),
+            else_: Box::new(else_.map(|e| folder.fold_expr(e))),
+        },
+        Expr::For { var, iter, body } => Expr::For {
+            var: Box::new(folder.fold_expr(*var)),
+            iter: Box::new(folder.fold_expr(*iter)),
+            body: Box::new(folder.fold_expr(*body)),
+        },
+        Expr::Where { body, bindings } => Expr::Where {
+            body: Box::new(folder.fold_expr(*body)),
+            bindings: bindings
+                .into_iter()
+                .map(|(name, value)| (folder.fold_expr(name), folder.fold_expr(value)))
+                .collect(),
+        },
+        Expr::While { expr, body } => Expr::While {
+            expr: Box::new(folder.fold_expr(*expr)),
+            body: Box::new(folder.fold_expr(*body)),
+        },
+        Expr::Match { expr, arms } => Expr::Match {
+            expr: Box::new(folder.fold_expr(*expr)),
+            arms: arms
+                .into_iter()
+                .map(|arm| MatchArm {
+                    pattern: arm.pattern,
+                    guard: arm.guard.map(|g| folder.fold_expr(g)),
+                    body: folder.fold_expr(arm.body),
+                })
+                .collect(),
+        },
+    };
+
+    (expr, span)
+}
@@ -0,0 +1,77 @@
+//! Lists the identifiers a program refers to, for tools such as unused
+//! variable checks and rename previews.
+
+use crate::parser::ast::{Expr, Statement};
+use crate::parser::visit::{self, Visitor};
+use crate::span::{Span, Spanned};
+
+#[derive(Default)]
+struct Idents(Vec<(String, Span)>);
+
+impl Visitor<'_> for Idents {
+    fn visit_statement(&mut self, statement: &Spanned<Statement>) {
+        // The names being declared aren't references, so only the rest of
+        // a declaration is visited.
+        match &statement.0 {
+            Statement::Let { value, .. } => self.visit_expr(value),
+            Statement::Function { params, body, .. } => {
+                for default in params.iter().filter_map(|p| p.default.as_ref()) {
+                    self.visit_expr(default);
+                }
+                self.visit_expr(body);
+            }
+            Statement::Signature { params, .. } => {
+                for default in params.iter().filter_map(|p| p.default.as_ref()) {
+                    self.visit_expr(default);
+                }
+            }
+            Statement::TypeAlias { .. } | Statement::Record { .. } => {}
+            statement => visit::walk_statement(self, statement),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Spanned<Expr>) {
+        match &expr.0 {
+            Expr::Ident(name) => self.0.push((name.clone(), expr.1)),
+            // A loop's variable is declared by it, like a `let`.
+            Expr::For { iter, body, .. } => {
+                self.visit_expr(iter);
+                self.visit_expr(body);
+            }
+            Expr::Where { body, bindings } => {
+                self.visit_expr(body);
+                for (_, value) in bindings {
+                    self.visit_expr(value);
+                }
+            }
+            expr => visit::walk_expr(self, expr),
+        }
+    }
+}
+
+/// Returns every identifier referred to in `stmts`, along with its span.
+///
+/// Identifiers are listed in the order they're written. The names given to
+/// variables, loop variables, functions and types where they're declared
+/// aren't included.
+///
+/// ```
+/// use alpaca::parser::idents::collect_idents;
+/// use alpaca::parser::Parser;
+/// use alpaca::span::Span;
+///
+/// let tree = Parser::new("let x = 1 x + x", "example").parse().unwrap();
+/// assert_eq!(
+///     collect_idents(&tree),
+///     [
+///         ("x".to_string(), Span::from(10..11)),
+///         ("x".to_string(), Span::from(14..15)),
+///     ]
+/// );
+/// ```
+#[must_use]
+pub fn collect_idents(stmts: &[Spanned<Statement>]) -> Vec<(String, Span)> {
+    let mut idents = Idents::default();
+    visit::walk_statements(&mut idents, stmts);
+    idents.0
+}
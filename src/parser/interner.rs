@@ -0,0 +1,91 @@
+//! Interns identifier names, so a name used many times is stored once and
+//! can be compared by a small `Symbol` rather than by its text.
+
+use std::collections::HashMap;
+
+use crate::parser::ast::Statement;
+use crate::parser::idents::collect_idents;
+use crate::span::{Span, Spanned};
+
+/// A name stored in an `Interner`. Symbols from the same interner are equal
+/// exactly when their names are.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Symbol(u32);
+
+/// Hands out a `Symbol` for each distinct name.
+///
+/// ```
+/// use alpaca::parser::interner::Interner;
+///
+/// let mut interner = Interner::default();
+/// let foo = interner.intern("foo");
+/// assert_eq!(interner.intern("foo"), foo);
+/// assert_ne!(interner.intern("bar"), foo);
+/// assert_eq!(interner.resolve(foo), "foo");
+/// ```
+#[derive(Debug, Default)]
+pub struct Interner {
+    symbols: HashMap<String, Symbol>,
+    names: Vec<String>,
+}
+
+impl Interner {
+    /// Returns the symbol for `name`, storing it if it hasn't been seen.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` distinct names are interned.
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(name) {
+            return symbol;
+        }
+
+        let symbol = Symbol(u32::try_from(self.names.len()).expect("too many names to intern"));
+        self.symbols.insert(name.to_string(), symbol);
+        self.names.push(name.to_string());
+        symbol
+    }
+
+    /// Returns the name a symbol stands for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the symbol came from a different interner.
+    #[must_use]
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.names[symbol.0 as usize]
+    }
+
+    /// The number of distinct names interned.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Interns every identifier referred to in `stmts`, as listed by
+    /// `collect_idents`, returning their symbols along with their spans.
+    ///
+    /// ```
+    /// use alpaca::parser::interner::Interner;
+    /// use alpaca::parser::Parser;
+    ///
+    /// let tree = Parser::new("let x = 1 x + x", "example").parse().unwrap();
+    /// let mut interner = Interner::default();
+    /// let idents = interner.intern_idents(&tree);
+    ///
+    /// assert_eq!(idents.len(), 2);
+    /// assert_eq!(idents[0].0, idents[1].0);
+    /// assert_eq!(interner.len(), 1);
+    /// ```
+    pub fn intern_idents(&mut self, stmts: &[Spanned<Statement>]) -> Vec<(Symbol, Span)> {
+        collect_idents(stmts)
+            .into_iter()
+            .map(|(name, span)| (self.intern(&name), span))
+            .collect()
+    }
+}
@@ -0,0 +1,51 @@
+//! Finds the AST node under a position in the source, for editor features
+//! such as hover and go to definition.
+//!
+//! Relies on every node's span containing the spans of its children.
+
+use crate::parser::ast::{Expr, Statement};
+use crate::parser::visit::{self, Visitor};
+use crate::span::Spanned;
+
+struct NodeAt<'ast> {
+    offset: usize,
+    found: Option<&'ast Expr>,
+}
+
+impl<'ast> Visitor<'ast> for NodeAt<'ast> {
+    fn visit_statement(&mut self, statement: &'ast Spanned<Statement>) {
+        if statement.1.contains(self.offset) {
+            visit::walk_statement(self, &statement.0);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Spanned<Expr>) {
+        // Children are visited after their parent, so the last expression
+        // found is the innermost one.
+        if expr.1.contains(self.offset) {
+            self.found = Some(&expr.0);
+            visit::walk_expr(self, &expr.0);
+        }
+    }
+}
+
+/// Returns the innermost expression whose span contains the byte `offset`,
+/// or `None` if the offset isn't inside any expression.
+///
+/// ```
+/// use alpaca::parser::ast::{Expr, LiteralKind};
+/// use alpaca::parser::lookup::node_at;
+/// use alpaca::parser::Parser;
+///
+/// let tree = Parser::new("1 + 2", "example").parse().unwrap();
+/// assert_eq!(node_at(&tree, 4), Some(&Expr::Literal(LiteralKind::Int(2, None))));
+/// ```
+#[must_use]
+pub fn node_at(tree: &[Spanned<Statement>], offset: usize) -> Option<&Expr> {
+    let mut lookup = NodeAt {
+        offset,
+        found: None,
+    };
+    visit::walk_statements(&mut lookup, tree);
+    lookup.found
+}
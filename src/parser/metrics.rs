@@ -0,0 +1,77 @@
+//! Size and complexity metrics over the AST, for tools such as linters which
+//! warn about overly complex code.
+//!
+//! Both expressions and statements count as nodes.
+
+use crate::parser::ast::{Expr, Statement};
+use crate::parser::visit::{self, Visitor};
+use crate::span::Spanned;
+
+#[derive(Default)]
+struct Metrics {
+    count: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Metrics {
+    fn enter(&mut self) {
+        self.count += 1;
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+    }
+}
+
+impl Visitor<'_> for Metrics {
+    fn visit_statement(&mut self, statement: &Spanned<Statement>) {
+        self.enter();
+        visit::walk_statement(self, &statement.0);
+        self.depth -= 1;
+    }
+
+    fn visit_expr(&mut self, expr: &Spanned<Expr>) {
+        self.enter();
+        visit::walk_expr(self, &expr.0);
+        self.depth -= 1;
+    }
+}
+
+fn measure(expr: &Expr) -> Metrics {
+    let mut metrics = Metrics::default();
+    metrics.enter();
+    visit::walk_expr(&mut metrics, expr);
+    metrics
+}
+
+/// Counts the nodes in an expression, including the expression itself.
+///
+/// `1 + 2 * 3` has 5 nodes.
+///
+/// ```
+/// use alpaca::parser::metrics::node_count;
+/// use alpaca::parser::Parser;
+///
+/// let (expr, _) = Parser::new("1 + 2 * 3", "example").parse_expression(1).unwrap();
+/// assert_eq!(node_count(&expr), 5);
+/// ```
+#[must_use]
+pub fn node_count(expr: &Expr) -> usize {
+    measure(expr).count
+}
+
+/// Returns the number of nodes on the longest path from an expression down
+/// to a leaf, including the expression itself.
+///
+/// `1 + 2 * 3` has a depth of 3.
+///
+/// ```
+/// use alpaca::parser::metrics::depth;
+/// use alpaca::parser::Parser;
+///
+/// let (expr, _) = Parser::new("1 + 2 * 3", "example").parse_expression(1).unwrap();
+/// assert_eq!(depth(&expr), 3);
+/// ```
+#[must_use]
+pub fn depth(expr: &Expr) -> usize {
+    measure(expr).max_depth
+}
@@ -1,44 +1,482 @@
 //! The parser takes a string an outputs an Abstract Syntax Tree (AST).
 //! Alpaca's parser is implemented as a Pratt parser.
 
-mod ast;
+mod annotation;
+pub mod ast;
+pub mod const_eval;
+pub mod diagnostics;
 mod expression;
+pub mod fold;
+pub mod idents;
+pub mod interner;
+pub mod lookup;
+pub mod metrics;
+mod pattern;
+pub mod pretty;
+pub mod resolve;
+pub mod sexpr;
 mod statement;
+pub mod visit;
 
-use crate::lexer::Lexer;
+use std::fmt;
+use std::io::Read;
+
+use crate::lexer::{is_line_terminator, Lexer, LexerOptions, TokenTrivia, Trivia, TriviaKind};
+use crate::parser::ast::{Annotation, IntType, Statement};
 use crate::span::{Span, Spanned};
 use crate::tokens::TokenKind;
 
 /// Represents the different types of errors the parser
 /// may encounter.
+///
+/// Tokens are stored owned, so errors don't borrow from the source.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// Expected one of the given items, found something else.
-    Expected(Vec<TokenKind>, TokenKind, Span),
+    Expected(Vec<TokenKind<'static>>, TokenKind<'static>, Span),
+    /// Expected something which isn't a single token, described in words
+    /// such as `"identifier"`, found something else.
+    ExpectedItem(&'static str, TokenKind<'static>, Span),
     /// An unclosed delimeter.
-    Unclosed(TokenKind, Span),
+    Unclosed(TokenKind<'static>, Span),
     /// Found an unexpected token.
-    Unexpected(TokenKind, Span),
+    Unexpected(TokenKind<'static>, Span),
+    /// The source ended partway through a construct.
+    UnexpectedEof(Span),
+    /// Found a keyword where a name was expected, along with what the name
+    /// was for, such as `"variable"`.
+    Reserved(TokenKind<'static>, &'static str, Span),
     /// Another type of error occurred with the given message.
     Other(String, Span),
 }
 
+impl ErrorKind {
+    /// An `Unexpected` error for `token`, or `UnexpectedEof` if it's the end
+    /// of the file.
+    #[must_use]
+    pub fn unexpected(token: TokenKind<'_>, span: Span) -> Self {
+        match token {
+            TokenKind::EoF => Self::UnexpectedEof(span),
+            token => Self::Unexpected(token.into_owned(), span),
+        }
+    }
+
+    /// Whether the error was caused by the source ending early, which a REPL
+    /// can take as a sign to read another line of input.
+    ///
+    /// ```
+    /// use alpaca::parser::{ErrorKind, Parser};
+    ///
+    /// let error = Parser::new("let x =", "example").parse_statement().unwrap_err();
+    /// assert!(matches!(error.kind(), ErrorKind::UnexpectedEof(_)));
+    /// assert!(error.kind().is_eof());
+    ///
+    /// let error = Parser::new("let x", "example").parse_statement().unwrap_err();
+    /// assert!(error.kind().is_eof());
+    /// ```
+    #[must_use]
+    pub fn is_eof(&self) -> bool {
+        matches!(self, Self::UnexpectedEof(_)) || self.found() == Some(&TokenKind::EoF)
+    }
+
+    /// The span of source the error points at.
+    #[must_use]
+    pub const fn span(&self) -> Span {
+        match self {
+            Self::Expected(_, _, span)
+            | Self::ExpectedItem(_, _, span)
+            | Self::Unclosed(_, span)
+            | Self::Unexpected(_, span)
+            | Self::UnexpectedEof(span)
+            | Self::Reserved(_, _, span)
+            | Self::Other(_, span) => *span,
+        }
+    }
+
+    /// The token which was found, if this error is about finding the wrong one.
+    #[must_use]
+    pub const fn found(&self) -> Option<&TokenKind<'static>> {
+        match self {
+            Self::Expected(_, found, _)
+            | Self::ExpectedItem(_, found, _)
+            | Self::Unexpected(found, _)
+            | Self::Reserved(found, _, _) => Some(found),
+            Self::Unclosed(..) | Self::UnexpectedEof(_) | Self::Other(..) => None,
+        }
+    }
+}
+
+/// Quotes a token for an error message, except for the end of the file,
+/// which isn't any text in the source.
+fn quote(token: &TokenKind) -> String {
+    match token {
+        TokenKind::EoF => token.to_string(),
+        token => format!("`{token}`"),
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Expected(expected, found, _) => {
+                let expected: Vec<String> = expected.iter().map(quote).collect();
+                match expected.as_slice() {
+                    [expected] => write!(f, "expected {expected}")?,
+                    expected => write!(f, "expected one of {}", expected.join(", "))?,
+                }
+                write!(f, ", found {}", quote(found))
+            }
+            Self::ExpectedItem(expected, found, _) => {
+                write!(f, "expected {expected}, found {}", quote(found))
+            }
+            Self::Unclosed(delimiter, _) => write!(f, "unclosed {}", quote(delimiter)),
+            Self::Unexpected(found, _) => write!(f, "unexpected {}", quote(found)),
+            Self::UnexpectedEof(_) => f.write_str("unexpected end of file"),
+            Self::Reserved(keyword, name, _) => write!(
+                f,
+                "{} is a reserved keyword and can't be used as a {name} name",
+                quote(keyword)
+            ),
+            Self::Other(message, _) => f.write_str(message),
+        }
+    }
+}
+
 /// Parser error.
 #[derive(Debug)]
 pub struct ParserError {
     kind: ErrorKind,
     help: Option<String>,
+    notes: Vec<String>,
 }
 
 impl ParserError {
+    /// Creates a new error. Errors caused by reaching the end of the file
+    /// get a note saying so, since they point at the last token in the file.
+    ///
+    /// ```
+    /// use alpaca::parser::Parser;
+    /// use alpaca::span::Span;
+    ///
+    /// let error = Parser::new("let x =", "example").parse_statement().unwrap_err();
+    /// assert!(error.kind().is_eof());
+    /// assert_eq!(error.kind().span(), Span::from(6..7));
+    /// assert_eq!(error.notes(), ["reached end of file"]);
+    /// ```
+    #[must_use]
     pub fn new(kind: ErrorKind, help: Option<String>) -> Self {
-        Self { kind, help }
+        let notes = if kind.is_eof() {
+            vec!["reached end of file".to_string()]
+        } else {
+            Vec::new()
+        };
+
+        Self { kind, help, notes }
     }
 
+    #[must_use]
     pub fn with_help(self, help: String) -> Self {
         Self {
-            kind: self.kind,
             help: Some(help),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_note(mut self, note: String) -> Self {
+        self.notes.push(note);
+        self
+    }
+
+    #[must_use]
+    pub const fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    #[must_use]
+    pub fn help(&self) -> Option<&str> {
+        self.help.as_deref()
+    }
+
+    #[must_use]
+    pub fn notes(&self) -> &[String] {
+        &self.notes
+    }
+}
+
+/// Displays the error's message, followed by its help and notes each on
+/// their own line.
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error: {}", self.kind)?;
+        if let Some(help) = &self.help {
+            write!(f, "\n  help: {help}")?;
+        }
+        for note in &self.notes {
+            write!(f, "\n  note: {note}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParserError {}
+
+/// How serious a `Diagnostic` is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// Something to report about the source, from a hard error to a lint.
+///
+/// Unlike a `ParserError`, a warning or note doesn't stop the source from
+/// being parsed, so `Parser::parse_with_diagnostics` reports them all in one
+/// list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    pub help: Option<String>,
+    pub notes: Vec<String>,
+    /// A name for the kind of diagnostic, such as `"unreachable_code"`, for
+    /// tools to filter on.
+    pub code: Option<&'static str>,
+}
+
+impl From<ParserError> for Diagnostic {
+    fn from(error: ParserError) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: error.kind.to_string(),
+            span: error.kind.span(),
+            help: error.help,
+            notes: error.notes,
+            code: None,
+        }
+    }
+}
+
+/// Callbacks fired as the parser enters and leaves each rule, for tools
+/// such as parse tree visualizers. Both do nothing by default.
+///
+/// The rules are `"statement"` and `"expression"`. `on_enter` gets the span
+/// of the first token of the rule, and `on_exit` the span of what it parsed,
+/// or of the error if it failed.
+pub trait ParseObserver {
+    fn on_enter(&mut self, _rule: &'static str, _span: Span) {}
+
+    fn on_exit(&mut self, _rule: &'static str, _span: Span) {}
+}
+
+/// The result of parsing, with every error found on failure.
+pub type ParseResult<T> = Result<T, Vec<ParserError>>;
+
+/// A list of parser errors, displayed one after another.
+///
+/// ```
+/// use alpaca::parser::{ParseErrors, Parser};
+///
+/// let errors = Parser::new("let = 1 let y 2", "example").parse().unwrap_err();
+/// assert_eq!(
+///     ParseErrors(errors).to_string(),
+///     "error: expected identifier, found `=`\n\
+///      error: expected `=`, found `2`\n  help: Expected an `=` followed by a value."
+/// );
+/// ```
+#[derive(Debug)]
+pub struct ParseErrors(pub Vec<ParserError>);
+
+impl From<Vec<ParserError>> for ParseErrors {
+    fn from(errors: Vec<ParserError>) -> Self {
+        Self(errors)
+    }
+}
+
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{error}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseErrors {}
+
+/// Reads a whole program from `reader` and parses it, for tools which take
+/// their source from something like stdin.
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// use alpaca::parser::parse_reader;
+///
+/// let program = Cursor::new(b"let x = 1\nx + 2".to_vec());
+/// assert_eq!(parse_reader(program, "stdin").unwrap().len(), 2);
+/// ```
+///
+/// # Errors
+///
+/// Returns a single `ParserError` if the input can't be read or isn't valid
+/// UTF-8, otherwise every `ParserError` encountered while parsing.
+pub fn parse_reader(mut reader: impl Read, filename: &str) -> ParseResult<Vec<Spanned<Statement>>> {
+    let mut source = String::new();
+    reader.read_to_string(&mut source).map_err(|e| {
+        vec![ParserError::new(
+            ErrorKind::Other(format!("Couldn't read {filename}: {e}"), Span::from(0..0)),
+            None,
+        )]
+    })?;
+
+    Parser::new(&source, filename).parse()
+}
+
+/// Parses a single statement from the start of `source`, returning it along
+/// with the byte offset where parsing stopped, so the caller can deal with
+/// whatever follows.
+///
+/// ```
+/// use alpaca::parser::parse_one;
+///
+/// let source = "let x = 1 rest";
+/// let (statement, offset) = parse_one(source);
+/// assert!(statement.is_ok());
+/// assert_eq!(&source[offset..], "rest");
+/// ```
+///
+/// # Errors
+///
+/// The result holds the `ParserError` if the source doesn't start with a
+/// valid statement. The offset is then wherever the parser gave up, which is
+/// at or just after the offending token.
+pub fn parse_one(source: &str) -> (ParseResult<Spanned<Statement>>, usize) {
+    let mut parser = Parser::new(source, "");
+    let statement = parser.parse_statement().map_err(|e| vec![e]);
+    (statement, parser.offset())
+}
+
+/// Parses an interface file, which declares types and the signatures of
+/// functions without giving their bodies.
+///
+/// ```
+/// use alpaca::parser::ast::Statement;
+/// use alpaca::parser::parse_interface;
+///
+/// let source = "type Id = Int\nfun f(x: Int) -> Int";
+/// let statements = parse_interface(source, "example.alpacai").unwrap();
+/// assert!(matches!(statements[1].0, Statement::Signature { .. }));
+///
+/// assert!(parse_interface("fun f(x: Int) -> Int do x end", "example.alpacai").is_err());
+///
+/// // Anything else is reported once, and parsing carries on after it.
+/// let errors = parse_interface("let x = 1\ntype Id = Int", "example.alpacai").unwrap_err();
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(parse_interface("return 1", "example.alpacai").unwrap_err().len(), 1);
+/// ```
+///
+/// # Errors
+///
+/// Returns every `ParserError` encountered, including for function bodies
+/// and anything other than a type or function.
+pub fn parse_interface(source: &str, filename: &str) -> ParseResult<Vec<Spanned<Statement>>> {
+    let (statements, errors) =
+        Parser::new(source, filename).parse_recovering(Parser::parse_interface_statement);
+
+    if errors.is_empty() {
+        Ok(statements)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parses `source` as a single type annotation, with nothing else allowed
+/// after it.
+///
+/// ```
+/// use alpaca::parser::ast::Annotation;
+/// use alpaca::parser::parse_annotation_str;
+///
+/// let (annotation, _) = parse_annotation_str("(Int, String) -> Bool").unwrap();
+/// assert_eq!(
+///     annotation,
+///     Annotation::Function {
+///         arg_types: vec![
+///             Annotation::Single("Int".to_string()),
+///             Annotation::Single("String".to_string()),
+///         ],
+///         ret_type: Box::new(Annotation::Single("Bool".to_string())),
+///     }
+/// );
+///
+/// let errors = parse_annotation_str("Int String").unwrap_err();
+/// assert_eq!(
+///     errors[0].to_string(),
+///     "error: expected end of file, found `String`\n  help: Nothing can follow the annotation."
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Returns the `ParserError` if `source` isn't a valid annotation, or if
+/// anything follows it.
+pub fn parse_annotation_str(source: &str) -> ParseResult<Spanned<Annotation>> {
+    let mut parser = Parser::new(source, "");
+    let annotation = parser.parse_annotation().map_err(|e| vec![e])?;
+    parser
+        .consume(&TokenKind::EoF)
+        .map_err(|e| vec![e.with_help("Nothing can follow the annotation.".to_string())])?;
+    Ok(annotation)
+}
+
+/// Options controlling what the parser keeps from the source.
+// Each option is an independent switch, so they're best left as bools.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParserOptions {
+    /// Keep the whitespace and comments around each token, retrievable
+    /// through `Parser::trivia`.
+    pub preserve_trivia: bool,
+    /// Keep parentheses written around an expression as `Expr::Grouping`
+    /// nodes, rather than dropping them once they've been parsed.
+    pub preserve_grouping: bool,
+    /// The type integer literals without a suffix are range checked
+    /// against.
+    pub default_int: IntType,
+    /// Keep every token read from the source, retrievable through
+    /// `Parser::tokens`, so tools don't have to lex it a second time.
+    pub record_tokens: bool,
+    /// Fold `+`, `-` and `*` on two integer literals into a single literal
+    /// while parsing, so `2 + 3` becomes `5`. Operations which overflow
+    /// their type are left as they are.
+    pub fold_constants: bool,
+}
+
+/// Where the parser gets its tokens from.
+enum TokenSource<'a> {
+    Lexer(Lexer<'a>),
+    /// Tokens lexed ahead of time, see `Parser::from_tokens`.
+    Tokens(std::vec::IntoIter<Spanned<TokenKind<'a>>>),
+}
+
+impl<'a> TokenSource<'a> {
+    fn next_token_with_trivia(&mut self) -> (Spanned<TokenKind<'a>>, TokenTrivia<'a>) {
+        match self {
+            Self::Lexer(lexer) => lexer.next_token_with_trivia(),
+            Self::Tokens(tokens) => {
+                let token = tokens
+                    .next()
+                    .unwrap_or_else(|| (TokenKind::EoF, Span::from(0..0)));
+                (token, TokenTrivia::default())
+            }
         }
     }
 }
@@ -46,74 +484,600 @@ impl ParserError {
 /// Parses a string into an Abstract Syntax Tree (AST)
 pub struct Parser<'a> {
     source: &'a str,
-    lexer: Lexer<'a>,
+    tokens: TokenSource<'a>,
     filename: &'a str,
+    options: ParserOptions,
     current_token_span: Span,
-    peeked: Option<Spanned<TokenKind>>,
+    /// The span of the last token lexed before the end of the file.
+    last_token_span: Span,
+    peeked: Option<Spanned<TokenKind<'a>>>,
+    /// Doc comments written directly before the most recently lexed token.
+    docs: Option<String>,
+    /// The trivia around each token which has any, when it's being preserved.
+    trivia: Vec<Spanned<TokenTrivia<'a>>>,
+    /// Every token read so far, when they're being recorded.
+    recorded_tokens: Vec<Spanned<TokenKind<'a>>>,
+    diagnostics: Vec<Diagnostic>,
+    observer: Option<&'a mut dyn ParseObserver>,
 }
 
 impl<'a> Parser<'a> {
+    #[must_use]
     pub fn new(source: &'a str, filename: &'a str) -> Self {
+        Self::with_options(source, filename, ParserOptions::default())
+    }
+
+    #[must_use]
+    pub fn with_options(source: &'a str, filename: &'a str, options: ParserOptions) -> Self {
+        let lexer_options = LexerOptions {
+            preserve_trivia: options.preserve_trivia,
+        };
+
         Self {
             source,
-            lexer: Lexer::new(source),
+            tokens: TokenSource::Lexer(Lexer::with_options(source, lexer_options)),
             filename,
+            options,
             current_token_span: Span::from(0..0),
+            last_token_span: Span::from(0..0),
             peeked: None,
+            docs: None,
+            trivia: Vec::new(),
+            recorded_tokens: Vec::new(),
+            diagnostics: Vec::new(),
+            observer: None,
+        }
+    }
+
+    /// Creates a parser which reads from an already lexed list of tokens,
+    /// rather than lexing a source string itself. This lets tokens be
+    /// rewritten, for example by macro expansion, before they're parsed.
+    ///
+    /// The list doesn't need to end with `TokenKind::EoF`. With no source to
+    /// refer to, `source` returns an empty string.
+    ///
+    /// ```
+    /// use alpaca::parser::Parser;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let tokens = vec![
+    ///     (TokenKind::Integer("1".into()), (0..1).into()),
+    ///     (TokenKind::Plus, (2..3).into()),
+    ///     (TokenKind::Integer("2".into()), (4..5).into()),
+    /// ];
+    /// let expected = Parser::new("1 + 2", "example").parse().unwrap();
+    /// assert_eq!(Parser::from_tokens(tokens, "example").parse().unwrap(), expected);
+    /// ```
+    #[must_use]
+    pub fn from_tokens(tokens: Vec<Spanned<TokenKind<'a>>>, filename: &'a str) -> Self {
+        Self {
+            tokens: TokenSource::Tokens(tokens.into_iter()),
+            ..Self::new("", filename)
+        }
+    }
+
+    /// Reports each rule the parser enters and leaves to `observer`.
+    ///
+    /// ```
+    /// use alpaca::parser::{ParseObserver, Parser};
+    /// use alpaca::span::Span;
+    ///
+    /// #[derive(Default)]
+    /// struct Recorder(Vec<String>);
+    ///
+    /// impl ParseObserver for Recorder {
+    ///     fn on_enter(&mut self, rule: &'static str, span: Span) {
+    ///         self.0.push(format!("enter {rule} {}..{}", span.start, span.end));
+    ///     }
+    ///
+    ///     fn on_exit(&mut self, rule: &'static str, span: Span) {
+    ///         self.0.push(format!("exit {rule} {}..{}", span.start, span.end));
+    ///     }
+    /// }
+    ///
+    /// let mut recorder = Recorder::default();
+    /// Parser::new("1 + 2", "example")
+    ///     .with_observer(&mut recorder)
+    ///     .parse()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     recorder.0,
+    ///     [
+    ///         "enter statement 0..1",
+    ///         "enter expression 0..1",
+    ///         "enter expression 4..5",
+    ///         "exit expression 4..5",
+    ///         "exit expression 0..5",
+    ///         "exit statement 0..5",
+    ///     ]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_observer(self, observer: &'a mut dyn ParseObserver) -> Self {
+        Self {
+            observer: Some(observer),
+            ..self
+        }
+    }
+
+    /// Runs `parse` as the rule named `rule`, telling the observer, if
+    /// there is one, when it starts and finishes.
+    fn observe<T>(
+        &mut self,
+        rule: &'static str,
+        parse: impl FnOnce(&mut Self) -> Result<Spanned<T>, ParserError>,
+    ) -> Result<Spanned<T>, ParserError> {
+        if self.observer.is_none() {
+            return parse(self);
+        }
+
+        let start = self.peek()?.1;
+        if let Some(observer) = self.observer.as_mut() {
+            observer.on_enter(rule, start);
+        }
+        let result = parse(self);
+        let end = match &result {
+            Ok((_, span)) => *span,
+            Err(e) => e.kind.span(),
+        };
+        if let Some(observer) = self.observer.as_mut() {
+            observer.on_exit(rule, end);
+        }
+        result
+    }
+
+    /// Parses the whole source into a list of statements.
+    ///
+    /// On an error the parser synchronizes to the start of the next
+    /// statement and carries on, so every error in the source is reported.
+    ///
+    /// Source with nothing but whitespace and comments has no statements.
+    ///
+    /// ```
+    /// use alpaca::parser::Parser;
+    ///
+    /// for source in ["", "   ", "# comment"] {
+    ///     assert!(Parser::new(source, "example").parse().unwrap().is_empty());
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns every `ParserError` encountered if the source is invalid.
+    pub fn parse(&mut self) -> ParseResult<Vec<Spanned<Statement>>> {
+        let (statements, errors) = self.parse_recovering(Self::parse_statement);
+
+        if errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Parses the whole source like `parse`, but reports errors alongside
+    /// every other diagnostic in the order they appear in the source. The
+    /// statements which did parse are returned even if there are errors.
+    ///
+    /// ```
+    /// use alpaca::parser::{Parser, Severity};
+    ///
+    /// let source = "fun f() do return 1 print(2) end\nlet = 1";
+    /// let (statements, diagnostics) = Parser::new(source, "example").parse_with_diagnostics();
+    ///
+    /// assert_eq!(statements.len(), 1);
+    /// let severities: Vec<Severity> = diagnostics.iter().map(|d| d.severity).collect();
+    /// assert_eq!(severities, [Severity::Warning, Severity::Error]);
+    /// ```
+    pub fn parse_with_diagnostics(&mut self) -> (Vec<Spanned<Statement>>, Vec<Diagnostic>) {
+        let (statements, errors) = self.parse_recovering(Self::parse_statement);
+
+        let mut diagnostics = std::mem::take(&mut self.diagnostics);
+        diagnostics.extend(errors.into_iter().map(Diagnostic::from));
+        diagnostics.sort_by_key(|d| d.span.start);
+        (statements, diagnostics)
+    }
+
+    /// Parses every statement in the source with `parse_statement`,
+    /// synchronizing after each error.
+    fn parse_recovering(
+        &mut self,
+        parse_statement: fn(&mut Self) -> Result<Spanned<Statement>, ParserError>,
+    ) -> (Vec<Spanned<Statement>>, Vec<ParserError>) {
+        let mut statements = Vec::new();
+        let mut errors = Vec::new();
+
+        while !self.at_end() {
+            match parse_statement(self) {
+                Ok(statement) => statements.push(statement),
+                Err(e) => {
+                    errors.push(e);
+                    self.synchronize();
+                }
+            }
+        }
+
+        (statements, errors)
+    }
+
+    /// The source being parsed.
+    #[must_use]
+    pub const fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The name of the file being parsed.
+    #[must_use]
+    pub const fn filename(&self) -> &'a str {
+        self.filename
+    }
+
+    /// The diagnostics found so far which didn't stop parsing, such as
+    /// warnings about code which can never run.
+    ///
+    /// ```
+    /// use alpaca::parser::{Parser, Severity};
+    ///
+    /// let mut parser = Parser::new("fun f() do return 1 print(2) end", "example");
+    /// parser.parse().unwrap();
+    ///
+    /// let [warning] = parser.diagnostics() else {
+    ///     panic!("expected exactly one diagnostic");
+    /// };
+    /// assert_eq!(warning.severity, Severity::Warning);
+    /// assert_eq!(warning.message, "unreachable code");
+    /// ```
+    #[must_use]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// The trivia around each token read so far, paired with the span of the
+    /// token it's attached to. Always empty unless
+    /// `ParserOptions::preserve_trivia` is set.
+    ///
+    /// Comments are kept out of the token stream, so when preserved they show
+    /// up here as trivia. A comment on the same line as the token before it
+    /// trails that token, otherwise it leads the next one.
+    ///
+    /// ```
+    /// use alpaca::lexer::Trivia;
+    /// use alpaca::parser::{Parser, ParserOptions};
+    ///
+    /// let options = ParserOptions {
+    ///     preserve_trivia: true,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let mut parser = Parser::with_options("x # one\n/* two */ y", "example", options);
+    /// parser.parse().unwrap();
+    ///
+    /// let [(x, _), (y, _)] = parser.trivia() else {
+    ///     panic!("expected trivia around both tokens");
+    /// };
+    /// fn texts<'a>(trivia: &[Trivia<'a>]) -> Vec<&'a str> {
+    ///     trivia.iter().map(|t| t.text).collect()
+    /// }
+    /// assert_eq!(texts(&x.trailing), [" ", "# one"]);
+    /// assert_eq!(texts(&y.leading), ["\n", "/* two */", " "]);
+    /// ```
+    ///
+    /// Along with the recorded tokens, the trivia is enough to print the
+    /// source back out exactly, comments included. Whatever follows the
+    /// last token is attached to the end of the file, at an empty span.
+    ///
+    /// ```
+    /// use alpaca::parser::{Parser, ParserOptions};
+    /// use alpaca::span::Span;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let source = "# header\nlet x = 1 # note\n\n# leading\nx+2 # end\n";
+    /// let options = ParserOptions {
+    ///     preserve_trivia: true,
+    ///     record_tokens: true,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let mut parser = Parser::with_options(source, "example", options);
+    /// parser.parse().unwrap();
+    ///
+    /// let eof = Span::from(source.len()..source.len());
+    /// let spans = parser
+    ///     .tokens()
+    ///     .iter()
+    ///     .filter(|(token, _)| !matches!(token, TokenKind::Comment(_)))
+    ///     .map(|(_, span)| *span)
+    ///     .chain([eof]);
+    ///
+    /// let mut reprinted = String::new();
+    /// for span in spans {
+    ///     let trivia = parser.trivia().iter().find(|(_, s)| *s == span);
+    ///     if let Some((trivia, _)) = trivia {
+    ///         trivia.leading.iter().for_each(|t| reprinted.push_str(t.text));
+    ///     }
+    ///     reprinted.push_str(&source[span.start..span.end]);
+    ///     if let Some((trivia, _)) = trivia {
+    ///         trivia.trailing.iter().for_each(|t| reprinted.push_str(t.text));
+    ///     }
+    /// }
+    /// assert_eq!(reprinted, source);
+    /// ```
+    #[must_use]
+    pub fn trivia(&self) -> &[Spanned<TokenTrivia<'a>>] {
+        &self.trivia
+    }
+
+    /// Every token read so far, including doc comments but not the end of
+    /// the file. Always empty unless `ParserOptions::record_tokens` is set.
+    ///
+    /// ```
+    /// use alpaca::lexer::tokenize;
+    /// use alpaca::parser::{Parser, ParserOptions};
+    ///
+    /// let source = "## Adds one.\nfun inc(x) do x + 1 end";
+    /// let options = ParserOptions {
+    ///     record_tokens: true,
+    ///     ..ParserOptions::default()
+    /// };
+    /// let mut parser = Parser::with_options(source, "example", options);
+    /// parser.parse().unwrap();
+    /// assert_eq!(parser.tokens(), tokenize(source));
+    /// ```
+    #[must_use]
+    pub fn tokens(&self) -> &[Spanned<TokenKind<'a>>] {
+        &self.recorded_tokens
+    }
+
+    /// Pulls the next token from the token source. Any doc comments in front of it
+    /// are stored in `docs`, replacing those read before the last token.
+    ///
+    /// The end of file is given the span of the last real token, so errors
+    /// caused by input ending early point at where it stopped.
+    fn next_token(&mut self) -> Spanned<TokenKind<'a>> {
+        let mut docs: Option<String> = None;
+        let mut leading = Vec::new();
+
+        loop {
+            let (token, trivia) = self.tokens.next_token_with_trivia();
+            leading.extend(trivia.leading);
+            if self.options.record_tokens && token.0 != TokenKind::EoF {
+                self.recorded_tokens.push(token.clone());
+            }
+
+            match token {
+                (TokenKind::Comment(_), span) => {
+                    if self.options.preserve_trivia {
+                        let comment = Trivia {
+                            kind: TriviaKind::Comment,
+                            text: &self.source[span.start..span.end],
+                            span,
+                        };
+                        if leading.is_empty() && docs.is_none() && self.trails_last_token(span) {
+                            self.push_trailing(comment, trivia.trailing);
+                        } else {
+                            leading.push(comment);
+                            leading.extend(trivia.trailing);
+                        }
+                    }
+                }
+                (TokenKind::DocComment(line), span) => {
+                    if self.options.preserve_trivia {
+                        leading.push(Trivia {
+                            kind: TriviaKind::Comment,
+                            text: &self.source[span.start..span.end],
+                            span,
+                        });
+                        leading.extend(trivia.trailing);
+                    }
+
+                    let line = line.strip_prefix(' ').unwrap_or(&line);
+                    match &mut docs {
+                        Some(docs) => {
+                            docs.push('\n');
+                            docs.push_str(line);
+                        }
+                        None => docs = Some(line.to_string()),
+                    }
+                }
+                token => {
+                    let trivia = TokenTrivia {
+                        leading,
+                        trailing: trivia.trailing,
+                    };
+                    if !trivia.is_empty() {
+                        self.trivia.push((trivia, token.1));
+                    }
+
+                    self.docs = docs;
+                    if token.0 == TokenKind::EoF {
+                        return (TokenKind::EoF, self.last_token_span);
+                    }
+
+                    self.last_token_span = token.1;
+                    return token;
+                }
+            }
         }
     }
 
-    fn advance(&mut self) -> Spanned<TokenKind> {
+    /// Checks whether the next token starts on the same line as the last
+    /// one consumed. Without any source, as with `Parser::from_tokens`,
+    /// every token counts as being on the same line.
+    fn next_on_same_line(&mut self) -> Result<bool, ParserError> {
+        let (start, end) = (self.current_token_span.end, self.peek()?.1.start);
+        Ok(self
+            .source
+            .get(start..end)
+            .is_none_or(|between| !between.contains(is_line_terminator)))
+    }
+
+    /// Checks whether a comment at `span` is on the same line as the last
+    /// token. Real tokens are never empty, so an empty `last_token_span`
+    /// means there's no token before it.
+    fn trails_last_token(&self, span: Span) -> bool {
+        let last = self.last_token_span;
+        last.start < last.end && !self.source[last.end..span.start].contains(is_line_terminator)
+    }
+
+    /// Adds a comment, and the trivia after it, to the trailing trivia of the
+    /// last token.
+    fn push_trailing(&mut self, comment: Trivia<'a>, rest: Vec<Trivia<'a>>) {
+        let last = self.last_token_span;
+        if !matches!(self.trivia.last(), Some((_, span)) if *span == last) {
+            self.trivia.push((TokenTrivia::default(), last));
+        }
+
+        let (trivia, _) = self.trivia.last_mut().unwrap();
+        trivia.trailing.push(comment);
+        trivia.trailing.extend(rest);
+    }
+
+    /// Consumes the next token. Error tokens from the lexer become a
+    /// `ParserError` here, so no parsing rule ever sees one.
+    ///
+    /// ```
+    /// use alpaca::parser::{ErrorKind, Parser};
+    /// use alpaca::span::Span;
+    ///
+    /// let errors = Parser::new("let x = @", "example").parse().unwrap_err();
+    /// let [error] = errors.as_slice() else {
+    ///     panic!("expected exactly one error");
+    /// };
+    /// assert!(matches!(error.kind(), ErrorKind::Other(message, span)
+    ///     if message == "Unknown character @" && *span == Span::from(8..9)));
+    /// ```
+    fn advance(&mut self) -> Result<Spanned<TokenKind<'a>>, ParserError> {
         // If a token has been peeked, return that. Otherwise, advance the lexer
         // and return the next token.
-        if let Some(t) = self.peeked.take() {
-            self.current_token_span = t.1;
-            t
-        } else {
-            let t = self.lexer.next_token();
-            self.current_token_span = t.1;
-            t
+        let token = self.peeked.take().unwrap_or_else(|| self.next_token());
+        self.current_token_span = token.1;
+
+        match token {
+            (TokenKind::Error(message), span) => {
+                Err(ParserError::new(ErrorKind::Other(message, span), None))
+            }
+            token => Ok(token),
+        }
+    }
+
+    fn peek(&mut self) -> Result<&Spanned<TokenKind<'a>>, ParserError> {
+        // If nothing has been peeked, grab the next token from the lexer and store
+        // it as the peeked value.
+        if self.peeked.is_none() {
+            self.peeked = Some(self.next_token());
+        }
+
+        // Safe to unwrap, a token was peeked above.
+        match self.peeked.as_ref().unwrap() {
+            (TokenKind::Error(message), span) => Err(ParserError::new(
+                ErrorKind::Other(message.clone(), *span),
+                None,
+            )),
+            token => Ok(token),
         }
     }
 
-    fn peek(&mut self) -> &Spanned<TokenKind> {
-        // If nothing has been peeked, advance and store that token as the peeked value.
+    /// The byte offset of the next token to be read, or the length of the
+    /// source once every token has been read.
+    fn offset(&mut self) -> usize {
         if self.peeked.is_none() {
-            self.peeked = Some(self.advance());
+            self.peeked = Some(self.next_token());
         }
 
-        self.peeked.as_ref().unwrap()
+        // Safe to unwrap, a token was peeked above.
+        match self.peeked.as_ref().unwrap() {
+            (TokenKind::EoF, _) => self.source.len(),
+            (_, span) => span.start,
+        }
     }
 
     fn at_end(&mut self) -> bool {
-        self.peek().0 == TokenKind::EoF
+        matches!(self.peek(), Ok((TokenKind::EoF, _)))
     }
 
-    fn consume(&mut self, expected: &TokenKind) -> Result<(), ParserError> {
-        let token = self.peek();
+    /// Consumes the next token if it's `expected`.
+    ///
+    /// Reaching the end of the file instead is an `UnexpectedEof` error,
+    /// with a note saying what was expected.
+    ///
+    /// ```
+    /// use alpaca::parser::{ErrorKind, Parser};
+    ///
+    /// let error = Parser::new("(1 + 2", "example").parse_expression(1).unwrap_err();
+    /// assert!(matches!(error.kind(), ErrorKind::UnexpectedEof(_)));
+    /// assert_eq!(error.notes(), ["reached end of file", "expected `)`"]);
+    /// ```
+    fn consume(&mut self, expected: &TokenKind<'a>) -> Result<(), ParserError> {
+        let token = self.peek()?;
 
         if token.0 == *expected {
-            self.advance(); // Next token was the expected one, so advance.
+            self.advance()?; // Next token was the expected one, so advance.
             return Ok(());
         }
 
+        if token.0 == TokenKind::EoF {
+            return Err(ParserError::new(ErrorKind::UnexpectedEof(token.1), None)
+                .with_note(format!("expected {}", quote(expected))));
+        }
+
         Err(ParserError::new(
-            ErrorKind::Expected(vec![expected.clone()], token.0.clone(), token.1),
+            ErrorKind::Expected(
+                vec![expected.clone().into_owned()],
+                token.0.clone().into_owned(),
+                token.1,
+            ),
             None,
         ))
     }
 
+    /// Checks whether the next token is the soft keyword `keyword`.
+    ///
+    /// Soft keywords such as `then` and `in` are lexed as identifiers, and
+    /// only act as keywords where the grammar expects one, so they can still
+    /// be used as names everywhere else.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Expr, Statement};
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (statement, _) = Parser::new("let in = 5", "example").parse_statement().unwrap();
+    /// let Statement::Let { name, .. } = statement else {
+    ///     panic!("expected a let statement");
+    /// };
+    /// assert_eq!(name.0, Expr::Ident("in".to_string()));
+    ///
+    /// let source = "let then = 1\nfor x in then do x end";
+    /// assert!(Parser::new(source, "example").parse().is_ok());
+    /// ```
+    fn at_soft_keyword(&mut self, keyword: &str) -> bool {
+        matches!(self.peek(), Ok((TokenKind::Ident(name), _)) if name == keyword)
+    }
+
+    fn consume_soft_keyword(&mut self, keyword: &'static str) -> Result<(), ParserError> {
+        if self.at_soft_keyword(keyword) {
+            self.advance()?;
+            return Ok(());
+        }
+
+        self.consume(&TokenKind::Ident(keyword.into()))
+    }
+
     fn synchronize(&mut self) {
         while !self.at_end() {
-            match self.peek().0 {
-                TokenKind::Fun
-                | TokenKind::Let
-                | TokenKind::Return
-                | TokenKind::If
-                | TokenKind::For
-                | TokenKind::While => break,
-                _ => self.advance(),
-            };
+            match self.peek() {
+                Ok((
+                    TokenKind::Fun
+                    | TokenKind::Pub
+                    | TokenKind::Let
+                    | TokenKind::Type
+                    | TokenKind::Return
+                    | TokenKind::If
+                    | TokenKind::For
+                    | TokenKind::While,
+                    _,
+                )) => break,
+                // Errors are skipped over along with everything else.
+                _ => {
+                    let _ = self.advance();
+                }
+            }
         }
     }
 }
@@ -0,0 +1,50 @@
+use crate::parser::ast::Pattern;
+use crate::parser::{ErrorKind, Parser, ParserError};
+use crate::span::{Span, Spanned};
+use crate::tokens::TokenKind;
+
+type PatternResult = Result<Spanned<Pattern>, ParserError>;
+
+impl Parser<'_> {
+    /// Parses a pattern.
+    ///
+    /// `_`, `n`, `10`, `(a, _)`
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParserError` if the tokens don't form a valid pattern.
+    pub fn parse_pattern(&mut self) -> PatternResult {
+        match self.advance()? {
+            (TokenKind::Ident(name), span) if name == "_" => Ok((Pattern::Wildcard, span)),
+            (TokenKind::Ident(name), span) => Ok((Pattern::Ident(name.into_owned()), span)),
+            (
+                kind @ (TokenKind::Integer(_)
+                | TokenKind::String(_)
+                | TokenKind::True
+                | TokenKind::False),
+                span,
+            ) => Ok((Pattern::Literal(self.literal_kind(kind, span)?), span)),
+            (TokenKind::OpenParen, span) => {
+                let mut patterns = Vec::new();
+
+                while self.peek()?.0 != TokenKind::CloseParen {
+                    patterns.push(self.parse_pattern()?);
+
+                    // Consume a comma if we haven't reached the end of the tuple.
+                    if self.peek()?.0 != TokenKind::CloseParen {
+                        self.consume(&TokenKind::Comma)
+                            .map_err(|e| e.with_help("Did you forget a comma?".to_string()))?;
+                    }
+                }
+                self.consume(&TokenKind::CloseParen)?;
+
+                let span = Span::from(span.start..self.current_token_span.end);
+                Ok((Pattern::Tuple(patterns), span))
+            }
+            (token, span) => Err(ParserError::new(
+                ErrorKind::unexpected(token, span),
+                Some("Expected a pattern.".to_string()),
+            )),
+        }
+    }
+}
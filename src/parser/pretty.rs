@@ -0,0 +1,537 @@
+//! A pretty printer which formats the AST back into idiomatic Alpaca.
+//!
+//! The AST is first turned into a `Doc`, a description of the text along
+//! with the places it's allowed to break across lines. Groups in the `Doc`
+//! are printed on one line where they fit within the width, and broken
+//! across lines otherwise, following Wadler's "A prettier printer".
+//!
+//! Parentheses aren't kept in the AST, so they're added back wherever the
+//! precedence of an expression needs them.
+
+use crate::parser::ast::{
+    Annotation, Expr, IntType, LiteralKind, MatchArm, Param, Pattern, Statement,
+};
+use crate::parser::expression::{infix_precedence, is_right_associative};
+use crate::span::Spanned;
+use crate::tokens::TokenKind;
+
+const INDENT: usize = 4;
+
+/// A document to be laid out.
+enum Doc {
+    Text(String),
+    /// A line break, or the given text if its group fits on one line.
+    Line(&'static str),
+    /// A line break, even if its group would otherwise fit on one line.
+    HardLine,
+    /// Indents the lines broken within the document.
+    Nest(Box<Self>),
+    /// Lays the document out on one line if it fits.
+    Group(Box<Self>),
+    Concat(Vec<Self>),
+}
+
+fn text(s: impl Into<String>) -> Doc {
+    Doc::Text(s.into())
+}
+
+fn nest(doc: Doc) -> Doc {
+    Doc::Nest(Box::new(doc))
+}
+
+fn group(doc: Doc) -> Doc {
+    Doc::Group(Box::new(doc))
+}
+
+/// Joins documents, putting `separator` between each of them.
+fn join(docs: impl IntoIterator<Item = Doc>, separator: impl Fn() -> Doc) -> Doc {
+    let mut joined = Vec::new();
+    for (i, doc) in docs.into_iter().enumerate() {
+        if i > 0 {
+            joined.push(separator());
+        }
+        joined.push(doc);
+    }
+    Doc::Concat(joined)
+}
+
+/// Comma separated items between `open` and `close`, which are broken onto
+/// their own indented lines if they don't fit on one.
+fn delimited(open: &str, items: Vec<Doc>, close: &str) -> Doc {
+    if items.is_empty() {
+        return text(format!("{open}{close}"));
+    }
+
+    group(Doc::Concat(vec![
+        text(open),
+        nest(Doc::Concat(vec![
+            Doc::Line(""),
+            join(items, || Doc::Concat(vec![text(","), Doc::Line(" ")])),
+        ])),
+        Doc::Line(""),
+        text(close),
+    ]))
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Checks whether `doc` fits in `width` columns when laid out on one line.
+fn fits(mut width: usize, doc: &Doc) -> bool {
+    let mut stack = vec![doc];
+
+    while let Some(doc) = stack.pop() {
+        let len = match doc {
+            Doc::Text(s) => s.chars().count(),
+            Doc::Line(flat) => flat.len(),
+            Doc::HardLine => return false,
+            Doc::Nest(doc) | Doc::Group(doc) => {
+                stack.push(doc);
+                0
+            }
+            Doc::Concat(docs) => {
+                stack.extend(docs.iter().rev());
+                0
+            }
+        };
+
+        match width.checked_sub(len) {
+            Some(remaining) => width = remaining,
+            None => return false,
+        }
+    }
+
+    true
+}
+
+fn layout(doc: &Doc, width: usize) -> String {
+    let mut out = String::new();
+    let mut column = 0;
+    let mut stack = vec![(0, Mode::Break, doc)];
+
+    while let Some((indent, mode, doc)) = stack.pop() {
+        match doc {
+            Doc::Text(s) => {
+                out.push_str(s);
+                column += s.chars().count();
+            }
+            Doc::Line(flat) if mode == Mode::Flat => {
+                out.push_str(flat);
+                column += flat.len();
+            }
+            Doc::Line(_) | Doc::HardLine => {
+                out.push('\n');
+                out.push_str(&" ".repeat(indent));
+                column = indent;
+            }
+            Doc::Nest(doc) => stack.push((indent + INDENT, mode, doc)),
+            Doc::Group(doc) => {
+                let mode = if mode == Mode::Flat || fits(width.saturating_sub(column), doc) {
+                    Mode::Flat
+                } else {
+                    Mode::Break
+                };
+                stack.push((indent, mode, doc));
+            }
+            Doc::Concat(docs) => {
+                stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+            }
+        }
+    }
+
+    out
+}
+
+/// Formats statements as Alpaca source, breaking lines which would be longer
+/// than `width` where possible.
+///
+/// ```
+/// use alpaca::parser::pretty::format;
+/// use alpaca::parser::Parser;
+///
+/// let statements = Parser::new("fun f(x) do while x do x = x - 1 end end", "example")
+///     .parse()
+///     .unwrap();
+/// assert_eq!(
+///     format(&statements, 80),
+///     "fun f(x) do\n    while x do\n        x = x - 1\n    end\nend\n"
+/// );
+/// ```
+#[must_use]
+pub fn format(statements: &[Spanned<Statement>], width: usize) -> String {
+    if statements.is_empty() {
+        return String::new();
+    }
+
+    let doc = join(statements.iter().map(|s| statement(&s.0)), || Doc::HardLine);
+    let mut out = layout(&doc, width);
+    out.push('\n');
+    out
+}
+
+fn statement(statement: &Statement) -> Doc {
+    match statement {
+        Statement::Expression(e) => expr(&e.0),
+        Statement::Semi(e) => Doc::Concat(vec![expr(&e.0), text(";")]),
+        Statement::Return(e) => Doc::Concat(vec![text("return "), expr(&e.0)]),
+        Statement::Assert { cond, message } => {
+            let mut docs = vec![text("assert "), expr(&cond.0)];
+            if let Some(message) = message {
+                docs.extend([text(", "), expr(&message.0)]);
+            }
+            Doc::Concat(docs)
+        }
+        Statement::Break { label, value } => {
+            let mut docs = vec![text("break")];
+            if let Some(label) = label {
+                docs.push(text(format!(" '{}", label.0)));
+            }
+            if let Some(value) = value {
+                docs.extend([text(" "), expr(&value.0)]);
+            }
+            Doc::Concat(docs)
+        }
+        Statement::Continue => text("continue"),
+        Statement::Let {
+            name,
+            annotation,
+            value,
+        } => {
+            let mut docs = vec![text("let "), expr(&name.0)];
+            if let Some(annotation) = annotation {
+                docs.push(text(format!(": {}", annotation.0)));
+            }
+            docs.extend([text(" = "), expr(&value.0)]);
+            Doc::Concat(docs)
+        }
+        Statement::Function {
+            name,
+            docs,
+            public,
+            params,
+            return_annotation,
+            body,
+        } => {
+            let mut out = signature(
+                docs.as_deref(),
+                *public,
+                name,
+                params,
+                return_annotation.as_ref(),
+            );
+            out.extend([text(" "), expr(&body.0)]);
+            Doc::Concat(out)
+        }
+        Statement::Signature {
+            name,
+            docs,
+            public,
+            params,
+            return_annotation,
+        } => Doc::Concat(signature(
+            docs.as_deref(),
+            *public,
+            name,
+            params,
+            return_annotation.as_ref(),
+        )),
+        Statement::TypeAlias { name, target } => Doc::Concat(vec![
+            text("type "),
+            expr(&name.0),
+            text(format!(" = {}", target.0)),
+        ]),
+        Statement::Record { name, fields } => {
+            let fields = fields
+                .iter()
+                .map(|(field, annotation)| text(format!("{}: {}", field.0, annotation.0)))
+                .collect();
+            Doc::Concat(vec![
+                text("type "),
+                expr(&name.0),
+                text(" = "),
+                delimited("{ ", fields, " }"),
+            ])
+        }
+    }
+}
+
+/// A function up to its body, starting with its doc comments.
+fn signature(
+    docs: Option<&str>,
+    public: bool,
+    name: &Spanned<Expr>,
+    params: &[Param],
+    return_annotation: Option<&Spanned<Annotation>>,
+) -> Vec<Doc> {
+    let mut out = Vec::new();
+    for line in docs.iter().flat_map(|d| d.lines()) {
+        out.push(text(format!("## {line}").trim_end().to_string()));
+        out.push(Doc::HardLine);
+    }
+    if public {
+        out.push(text("pub "));
+    }
+    out.extend([
+        text("fun "),
+        expr(&name.0),
+        delimited("(", params.iter().map(param).collect(), ")"),
+    ]);
+    if let Some(annotation) = return_annotation {
+        out.push(text(format!(" -> {}", annotation.0)));
+    }
+    out
+}
+
+fn param(param: &Param) -> Doc {
+    let mut s = String::new();
+    if param.variadic {
+        s.push_str("...");
+    }
+    if param.mutable {
+        s.push_str("mut ");
+    }
+    if param.by_ref {
+        s.push_str("ref ");
+    }
+    s.push_str(&param.name.0);
+    if let Some(annotation) = &param.annotation {
+        s.push_str(": ");
+        s.push_str(&annotation.0.to_string());
+    }
+    match &param.default {
+        Some(default) => Doc::Concat(vec![text(s), text(" = "), expr(&default.0)]),
+        None => text(s),
+    }
+}
+
+/// The precedence an expression is parsed at, so a parent knows whether to
+/// wrap it in parentheses.
+fn precedence(e: &Expr) -> u8 {
+    match e {
+        Expr::Assignment { .. } | Expr::Where { .. } => 1,
+        // Without an `end`, the `else` branch runs as far as it can.
+        Expr::If { body, else_, .. } if !is_do_if(body, else_.as_ref().as_ref()) => 1,
+        Expr::Binary { op, .. } | Expr::Logical { op, .. } => infix_precedence(&op.0),
+        // 9 is the precedence level for the `!`, `-` and `+` unary operators.
+        Expr::Unary { .. } => 9,
+        _ => u8::MAX,
+    }
+}
+
+/// Formats `e`, wrapping it in parentheses if it binds less tightly than
+/// `min`.
+fn operand(e: &Expr, min: u8) -> Doc {
+    if precedence(e) < min {
+        Doc::Concat(vec![text("("), expr(e), text(")")])
+    } else {
+        expr(e)
+    }
+}
+
+/// Whether an `if` was written with `do`, rather than with `then`.
+fn is_do_if(body: &Spanned<Expr>, else_: Option<&Spanned<Expr>>) -> bool {
+    let is_block = |e: &Expr| matches!(e, Expr::Block { label: None, .. });
+    is_block(&body.0) && else_.is_none_or(|e| is_block(&e.0))
+}
+
+/// The lines of a block, each on its own indented line, followed by `end`.
+fn block_body(lines: Vec<Doc>) -> Doc {
+    if lines.is_empty() {
+        return text(" end");
+    }
+
+    Doc::Concat(vec![
+        nest(Doc::Concat(vec![
+            Doc::HardLine,
+            join(lines, || Doc::HardLine),
+        ])),
+        Doc::HardLine,
+        text("end"),
+    ])
+}
+
+/// Each statement of a block, followed by its tail.
+fn lines_of(e: &Expr) -> Vec<Doc> {
+    match e {
+        Expr::Block {
+            statements, tail, ..
+        } => statements
+            .iter()
+            .map(|s| statement(&s.0))
+            .chain(tail.iter().map(|t| expr(&t.0)))
+            .collect(),
+        _ => unreachable!("lines_of is only called on blocks."),
+    }
+}
+
+fn literal(literal: &LiteralKind) -> String {
+    match literal {
+        LiteralKind::Int(i, suffix) => format!("{i}{}", suffix.map_or("", IntType::suffix)),
+        LiteralKind::Bool(b) => b.to_string(),
+        // Braces are escaped by doubling them.
+        LiteralKind::String(s) => format!("\"{}\"", s.replace('{', "{{").replace('}', "}}")),
+    }
+}
+
+fn pattern(p: &Pattern) -> Doc {
+    match p {
+        Pattern::Wildcard => text("_"),
+        Pattern::Ident(name) => text(name),
+        Pattern::Literal(l) => text(literal(l)),
+        Pattern::Tuple(patterns) => {
+            delimited("(", patterns.iter().map(|p| pattern(&p.0)).collect(), ")")
+        }
+    }
+}
+
+fn arm(arm: &MatchArm) -> Doc {
+    let mut docs = vec![pattern(&arm.pattern.0)];
+    if let Some(guard) = &arm.guard {
+        docs.extend([text(" when "), expr(&guard.0)]);
+    }
+    docs.extend([text(" -> "), expr(&arm.body.0)]);
+    Doc::Concat(docs)
+}
+
+fn expr(e: &Expr) -> Doc {
+    match e {
+        Expr::Literal(l) => text(literal(l)),
+        Expr::Ident(name) => text(name),
+        Expr::Path(segments) => text(segments.join("::")),
+        Expr::Grouping(inner) => Doc::Concat(vec![text("("), expr(&inner.0), text(")")]),
+        Expr::Tuple(items) => delimited("(", items.iter().map(|i| expr(&i.0)).collect(), ")"),
+        Expr::Array(items) => delimited("[", items.iter().map(|i| expr(&i.0)).collect(), "]"),
+        Expr::Spread(inner) => Doc::Concat(vec![text("..."), expr(&inner.0)]),
+        Expr::Map { entries } => {
+            let entries = entries
+                .iter()
+                .map(|(k, v)| Doc::Concat(vec![expr(&k.0), text(": "), expr(&v.0)]))
+                .collect();
+            delimited("{", entries, "}")
+        }
+        Expr::Unary { op, rhs } => Doc::Concat(vec![text(op.0.to_string()), operand(&rhs.0, 9)]),
+        Expr::Binary { op, lhs, rhs } | Expr::Logical { op, lhs, rhs } => {
+            binary(&op.0, &lhs.0, &rhs.0)
+        }
+        Expr::Call { callee, args } => Doc::Concat(vec![
+            operand(&callee.0, infix_precedence(&TokenKind::OpenParen)),
+            delimited("(", args.iter().map(|a| expr(&a.0)).collect(), ")"),
+        ]),
+        Expr::Field { target, name } => Doc::Concat(vec![
+            operand(&target.0, infix_precedence(&TokenKind::Dot)),
+            text(format!(".{}", name.0)),
+        ]),
+        Expr::Assignment { name, value } => {
+            Doc::Concat(vec![expr(&name.0), text(" = "), expr(&value.0)])
+        }
+        Expr::Block { label, .. } => {
+            let label = label
+                .as_ref()
+                .map(|l| format!("'{}: ", l.0))
+                .unwrap_or_default();
+            Doc::Concat(vec![text(format!("{label}do")), block_body(lines_of(e))])
+        }
+        Expr::If {
+            condition,
+            body,
+            else_,
+        } => if_expr(&condition.0, body, else_.as_ref().as_ref()),
+        Expr::For { var, iter, body } => Doc::Concat(vec![
+            text("for "),
+            expr(&var.0),
+            text(" in "),
+            expr(&iter.0),
+            text(" "),
+            expr(&body.0),
+        ]),
+        Expr::Where { body, bindings } => {
+            let bindings = bindings.iter().enumerate().map(|(i, (name, value))| {
+                Doc::Concat(vec![
+                    text(if i == 0 { " " } else { ", " }),
+                    expr(&name.0),
+                    text(" = "),
+                    operand(&value.0, 2),
+                ])
+            });
+            Doc::Concat(
+                [operand(&body.0, 2), text(" where")]
+                    .into_iter()
+                    .chain(bindings)
+                    .collect(),
+            )
+        }
+        Expr::While { expr: cond, body } => Doc::Concat(vec![
+            text("while "),
+            expr(&cond.0),
+            text(" "),
+            expr(&body.0),
+        ]),
+        Expr::Match {
+            expr: scrutinee,
+            arms,
+        } => {
+            let mut docs = vec![text("match "), expr(&scrutinee.0), text(" do")];
+            if arms.is_empty() {
+                docs.push(text(" end"));
+            } else {
+                docs.extend([
+                    nest(Doc::Concat(vec![
+                        Doc::HardLine,
+                        join(arms.iter().map(arm), || Doc::HardLine),
+                    ])),
+                    Doc::HardLine,
+                    text("end"),
+                ]);
+            }
+            Doc::Concat(docs)
+        }
+    }
+}
+
+/// Prints a binary operation, breaking the line after the operator if needed.
+fn binary(op: &TokenKind, lhs: &Expr, rhs: &Expr) -> Doc {
+    // The side an operator doesn't group towards needs parentheses around
+    // an operand at the same level.
+    let precedence = infix_precedence(op);
+    let (lhs_min, rhs_min) = if is_right_associative(op) {
+        (precedence + 1, precedence)
+    } else {
+        (precedence, precedence + 1)
+    };
+    group(Doc::Concat(vec![
+        operand(lhs, lhs_min),
+        text(format!(" {op}")),
+        nest(Doc::Concat(vec![Doc::Line(" "), operand(rhs, rhs_min)])),
+    ]))
+}
+
+/// An `if` is printed with `do` and `end` when its branches are blocks, and
+/// with `then` otherwise.
+fn if_expr(condition: &Expr, body: &Spanned<Expr>, else_: Option<&Spanned<Expr>>) -> Doc {
+    if !is_do_if(body, else_) {
+        let mut docs = vec![text("if "), expr(condition), text(" then "), expr(&body.0)];
+        if let Some(else_) = else_ {
+            docs.extend([text(" else "), expr(&else_.0)]);
+        }
+        return Doc::Concat(docs);
+    }
+
+    let mut docs = vec![text("if "), expr(condition), text(" do")];
+    match else_ {
+        Some(else_) => {
+            let body = lines_of(&body.0);
+            if !body.is_empty() {
+                docs.push(nest(Doc::Concat(vec![
+                    Doc::HardLine,
+                    join(body, || Doc::HardLine),
+                ])));
+            }
+            docs.extend([Doc::HardLine, text("else"), block_body(lines_of(&else_.0))]);
+        }
+        None => docs.push(block_body(lines_of(&body.0))),
+    }
+    Doc::Concat(docs)
+}
@@ -0,0 +1,199 @@
+//! Resolves each use of a name to where it's declared.
+//!
+//! Variables, functions and parameters are all looked up the same way. A
+//! `let` binding is visible from the statement after it to the end of its
+//! block, while functions are visible throughout the block they're declared
+//! in, so they can call each other in any order. Blocks, functions, loops
+//! match arms and `where` clauses each introduce a scope of their own.
+
+use std::collections::HashMap;
+
+use crate::parser::ast::{Expr, Param, Pattern, Statement};
+use crate::parser::visit::{self, Visitor};
+use crate::parser::{Diagnostic, Severity};
+use crate::span::{Span, Spanned};
+
+/// The result of resolving a program.
+#[derive(Debug, Default)]
+pub struct Resolution {
+    /// Maps the span of each use of a name to the span of the name where
+    /// it's declared.
+    pub links: HashMap<Span, Span>,
+    /// An error for each name used without being declared.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Default)]
+struct Resolver {
+    scopes: Vec<HashMap<String, Span>>,
+    resolution: Resolution,
+}
+
+impl Resolver {
+    fn declare(&mut self, name: &str, span: Span) {
+        self.scopes
+            .last_mut()
+            .expect("there's always a scope open")
+            .insert(name.to_string(), span);
+    }
+
+    /// Declares the identifier `expr`, which every declaration's name is.
+    fn declare_expr(&mut self, expr: &Spanned<Expr>) {
+        if let Expr::Ident(name) = &expr.0 {
+            self.declare(name, expr.1);
+        }
+    }
+
+    fn declare_pattern(&mut self, pattern: &Spanned<Pattern>) {
+        match &pattern.0 {
+            Pattern::Ident(name) => self.declare(name, pattern.1),
+            Pattern::Tuple(patterns) => {
+                for pattern in patterns {
+                    self.declare_pattern(pattern);
+                }
+            }
+            Pattern::Wildcard | Pattern::Literal(_) => {}
+        }
+    }
+
+    fn resolve(&mut self, name: &str, span: Span) {
+        let definition = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied());
+
+        match definition {
+            Some(definition) => {
+                self.resolution.links.insert(span, definition);
+            }
+            None => self.resolution.diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                message: format!("undefined variable `{name}`"),
+                span,
+                help: None,
+                notes: Vec::new(),
+                code: Some("undefined_variable"),
+            }),
+        }
+    }
+
+    /// Runs `f` in a new scope.
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(HashMap::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    /// Resolves a list of statements in the current scope, declaring its
+    /// functions first.
+    fn statements(&mut self, statements: &[Spanned<Statement>]) {
+        for (statement, _) in statements {
+            if let Statement::Function { name, .. } = statement {
+                self.declare_expr(name);
+            }
+        }
+        visit::walk_statements(self, statements);
+    }
+
+    /// Resolves the default values of parameters, each of which can refer to
+    /// the parameters before it, declaring them all.
+    fn params(&mut self, params: &[Param]) {
+        for param in params {
+            if let Some(default) = &param.default {
+                self.visit_expr(default);
+            }
+            self.declare(&param.name.0, param.name.1);
+        }
+    }
+}
+
+impl Visitor<'_> for Resolver {
+    fn visit_statement(&mut self, statement: &Spanned<Statement>) {
+        match &statement.0 {
+            Statement::Let { name, value, .. } => {
+                self.visit_expr(value);
+                self.declare_expr(name);
+            }
+            Statement::Function { params, body, .. } => self.scoped(|resolver| {
+                resolver.params(params);
+                resolver.visit_expr(body);
+            }),
+            Statement::Signature { params, .. } => {
+                self.scoped(|resolver| resolver.params(params));
+            }
+            // Types live apart from variables.
+            Statement::TypeAlias { .. } | Statement::Record { .. } => {}
+            statement => visit::walk_statement(self, statement),
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Spanned<Expr>) {
+        match &expr.0 {
+            Expr::Ident(name) => self.resolve(name, expr.1),
+            Expr::Block {
+                statements, tail, ..
+            } => self.scoped(|resolver| {
+                resolver.statements(statements);
+                if let Some(tail) = tail {
+                    resolver.visit_expr(tail);
+                }
+            }),
+            Expr::For { var, iter, body } => {
+                self.visit_expr(iter);
+                self.scoped(|resolver| {
+                    resolver.declare_expr(var);
+                    resolver.visit_expr(body);
+                });
+            }
+            Expr::Where { body, bindings } => self.scoped(|resolver| {
+                for (name, value) in bindings {
+                    resolver.visit_expr(value);
+                    resolver.declare_expr(name);
+                }
+                resolver.visit_expr(body);
+            }),
+            Expr::Match { expr, arms } => {
+                self.visit_expr(expr);
+                for arm in arms {
+                    self.scoped(|resolver| {
+                        resolver.declare_pattern(&arm.pattern);
+                        if let Some(guard) = &arm.guard {
+                            resolver.visit_expr(guard);
+                        }
+                        resolver.visit_expr(&arm.body);
+                    });
+                }
+            }
+            expr => visit::walk_expr(self, expr),
+        }
+    }
+}
+
+/// Links each use of a name in `stmts` to its declaration, reporting an
+/// error for each name which isn't declared.
+///
+/// ```
+/// use alpaca::parser::resolve::resolve;
+/// use alpaca::parser::Parser;
+/// use alpaca::span::Span;
+///
+/// let tree = Parser::new("let x = 1 x", "example").parse().unwrap();
+/// let resolution = resolve(&tree);
+/// assert_eq!(resolution.links[&Span::from(10..11)], Span::from(4..5));
+/// assert!(resolution.diagnostics.is_empty());
+///
+/// let tree = Parser::new("y", "example").parse().unwrap();
+/// let resolution = resolve(&tree);
+/// assert_eq!(resolution.diagnostics[0].message, "undefined variable `y`");
+///
+/// // A block's bindings go out of scope at its `end`.
+/// let tree = Parser::new("do let z = 1 end z", "example").parse().unwrap();
+/// assert_eq!(resolve(&tree).diagnostics.len(), 1);
+/// ```
+#[must_use]
+pub fn resolve(stmts: &[Spanned<Statement>]) -> Resolution {
+    let mut resolver = Resolver::default();
+    resolver.scoped(|resolver| resolver.statements(stmts));
+    resolver.resolution
+}
@@ -0,0 +1,266 @@
+//! Prints the AST as Lisp-like S-expressions, such as `(+ 1 (* 2 3))`.
+//!
+//! The output leaves out spans, so it's much terser than `Debug` and makes
+//! for stable snapshots in tests.
+
+use crate::parser::ast::{Expr, IntType, LiteralKind, MatchArm, Param, Pattern, Statement};
+use crate::span::Spanned;
+use crate::tokens::TokenKind;
+
+/// Prints an expression as an S-expression.
+///
+/// ```
+/// use alpaca::parser::sexpr::to_sexpr;
+/// use alpaca::parser::Parser;
+///
+/// let (expr, _) = Parser::new("1 + 2 * 3", "example").parse_expression(1).unwrap();
+/// assert_eq!(to_sexpr(&expr), "(+ 1 (* 2 3))");
+/// ```
+#[must_use]
+pub fn to_sexpr(expr: &Expr) -> String {
+    match expr {
+        Expr::Literal(literal) => literal_to_sexpr(literal),
+        Expr::Ident(name) => name.clone(),
+        Expr::Path(segments) => segments.join("::"),
+        Expr::Grouping(expr) => list("group", [to_sexpr(&expr.0)]),
+        Expr::Tuple(items) => list("tuple", items.iter().map(|i| to_sexpr(&i.0))),
+        Expr::Array(items) => list("array", items.iter().map(|i| to_sexpr(&i.0))),
+        Expr::Spread(expr) => list("...", [to_sexpr(&expr.0)]),
+        Expr::Map { entries } => list(
+            "map",
+            entries
+                .iter()
+                .map(|(k, v)| format!("({} {})", to_sexpr(&k.0), to_sexpr(&v.0))),
+        ),
+        Expr::Unary { op, rhs } => list(operator(&op.0), [to_sexpr(&rhs.0)]),
+        Expr::Binary { op, lhs, rhs } | Expr::Logical { op, lhs, rhs } => {
+            list(operator(&op.0), [to_sexpr(&lhs.0), to_sexpr(&rhs.0)])
+        }
+        Expr::Call { callee, args } => list(
+            "call",
+            std::iter::once(to_sexpr(&callee.0)).chain(args.iter().map(|a| to_sexpr(&a.0))),
+        ),
+        Expr::Field { target, name } => list(".", [to_sexpr(&target.0), name.0.clone()]),
+        Expr::Assignment { name, value } => list("=", [to_sexpr(&name.0), to_sexpr(&value.0)]),
+        Expr::Block {
+            label,
+            statements,
+            tail,
+        } => list(
+            "do",
+            label
+                .iter()
+                .map(|l| format!("'{}", l.0))
+                .chain(statements.iter().map(|s| statement_to_sexpr(&s.0)))
+                .chain(tail.iter().map(|t| to_sexpr(&t.0))),
+        ),
+        Expr::If {
+            condition,
+            body,
+            else_,
+        } => list(
+            "if",
+            [to_sexpr(&condition.0), to_sexpr(&body.0)]
+                .into_iter()
+                .chain(else_.iter().map(|e| to_sexpr(&e.0))),
+        ),
+        Expr::For { var, iter, body } => list(
+            "for",
+            [to_sexpr(&var.0), to_sexpr(&iter.0), to_sexpr(&body.0)],
+        ),
+        Expr::Where { body, bindings } => {
+            list(
+                "where",
+                std::iter::once(to_sexpr(&body.0)).chain(bindings.iter().map(|(name, value)| {
+                    format!("({} {})", to_sexpr(&name.0), to_sexpr(&value.0))
+                })),
+            )
+        }
+        Expr::While { expr, body } => list("while", [to_sexpr(&expr.0), to_sexpr(&body.0)]),
+        Expr::Match { expr, arms } => list(
+            "match",
+            std::iter::once(to_sexpr(&expr.0)).chain(arms.iter().map(arm_to_sexpr)),
+        ),
+    }
+}
+
+/// Prints a statement as an S-expression. Expression statements are
+/// printed as just the expression, or as `(; <expr>)` when they end in `;`.
+///
+/// ```
+/// use alpaca::parser::sexpr::statement_to_sexpr;
+/// use alpaca::parser::Parser;
+///
+/// let (statement, _) = Parser::new("let x: Int = -1", "example").parse_statement().unwrap();
+/// assert_eq!(statement_to_sexpr(&statement), "(let x Int (- 1))");
+/// ```
+#[must_use]
+pub fn statement_to_sexpr(statement: &Statement) -> String {
+    match statement {
+        Statement::Expression(expr) => to_sexpr(&expr.0),
+        Statement::Semi(expr) => list(";", [to_sexpr(&expr.0)]),
+        Statement::Return(expr) => list("return", [to_sexpr(&expr.0)]),
+        Statement::Assert { cond, message } => list(
+            "assert",
+            std::iter::once(to_sexpr(&cond.0)).chain(message.iter().map(|m| to_sexpr(&m.0))),
+        ),
+        Statement::Break { label, value } => list(
+            "break",
+            label
+                .iter()
+                .map(|l| format!("'{}", l.0))
+                .chain(value.iter().map(|v| to_sexpr(&v.0))),
+        ),
+        Statement::Continue => "(continue)".to_string(),
+        Statement::Let {
+            name,
+            annotation,
+            value,
+        } => list(
+            "let",
+            std::iter::once(to_sexpr(&name.0))
+                .chain(annotation.iter().map(|a| a.0.to_string()))
+                .chain(std::iter::once(to_sexpr(&value.0))),
+        ),
+        Statement::Function {
+            name,
+            public,
+            params,
+            return_annotation,
+            body,
+            ..
+        } => {
+            let params = params.iter().map(param_to_sexpr);
+
+            list(
+                if *public { "pub fun" } else { "fun" },
+                [to_sexpr(&name.0), list("", params)]
+                    .into_iter()
+                    .chain(return_annotation.iter().map(|a| format!("-> {}", a.0)))
+                    .chain(std::iter::once(to_sexpr(&body.0))),
+            )
+        }
+        Statement::Signature {
+            name,
+            public,
+            params,
+            return_annotation,
+            ..
+        } => {
+            let params = params.iter().map(param_to_sexpr);
+
+            list(
+                if *public { "pub sig" } else { "sig" },
+                [to_sexpr(&name.0), list("", params)]
+                    .into_iter()
+                    .chain(return_annotation.iter().map(|a| format!("-> {}", a.0))),
+            )
+        }
+        Statement::TypeAlias { name, target } => {
+            list("type", [to_sexpr(&name.0), target.0.to_string()])
+        }
+        Statement::Record { name, fields } => list(
+            "record",
+            std::iter::once(to_sexpr(&name.0)).chain(
+                fields
+                    .iter()
+                    .map(|(field, a)| format!("({} {})", field.0, a.0)),
+            ),
+        ),
+    }
+}
+
+/// Builds `(head item item ...)`, leaving out the head if it's empty.
+fn list(head: &str, items: impl IntoIterator<Item = String>) -> String {
+    let items: Vec<String> = if head.is_empty() {
+        items.into_iter().collect()
+    } else {
+        std::iter::once(head.to_string()).chain(items).collect()
+    };
+    format!("({})", items.join(" "))
+}
+
+fn literal_to_sexpr(literal: &LiteralKind) -> String {
+    match literal {
+        LiteralKind::Int(i, suffix) => format!("{i}{}", suffix.map_or("", IntType::suffix)),
+        LiteralKind::Bool(b) => b.to_string(),
+        LiteralKind::String(s) => format!("{s:?}"),
+    }
+}
+
+/// Parameters with no modifiers or annotation are printed as just their
+/// name, otherwise as `(... mut ref <name> <annotation>)`. A default value wraps
+/// that as `(= <param> <default>)`.
+fn param_to_sexpr(param: &Param) -> String {
+    if let Some(default) = &param.default {
+        return list("=", [bare_param_to_sexpr(param), to_sexpr(&default.0)]);
+    }
+    bare_param_to_sexpr(param)
+}
+
+fn bare_param_to_sexpr(param: &Param) -> String {
+    if !param.variadic && !param.mutable && !param.by_ref && param.annotation.is_none() {
+        return param.name.0.clone();
+    }
+
+    let modifiers = [
+        (param.variadic, "..."),
+        (param.mutable, "mut"),
+        (param.by_ref, "ref"),
+    ]
+    .into_iter()
+    .filter(|(set, _)| *set)
+    .map(|(_, modifier)| modifier.to_string());
+    list(
+        "",
+        modifiers
+            .chain(std::iter::once(param.name.0.clone()))
+            .chain(param.annotation.iter().map(|a| a.0.to_string())),
+    )
+}
+
+fn pattern_to_sexpr(pattern: &Spanned<Pattern>) -> String {
+    match &pattern.0 {
+        Pattern::Wildcard => "_".to_string(),
+        Pattern::Ident(name) => name.clone(),
+        Pattern::Literal(literal) => literal_to_sexpr(literal),
+        Pattern::Tuple(patterns) => list("tuple", patterns.iter().map(pattern_to_sexpr)),
+    }
+}
+
+/// Arms are printed as `(<pattern> when <guard> -> <body>)`, where the
+/// guard is optional.
+fn arm_to_sexpr(arm: &MatchArm) -> String {
+    let guard = arm
+        .guard
+        .as_ref()
+        .map(|g| format!(" when {}", to_sexpr(&g.0)))
+        .unwrap_or_default();
+    format!(
+        "({}{guard} -> {})",
+        pattern_to_sexpr(&arm.pattern),
+        to_sexpr(&arm.body.0)
+    )
+}
+
+/// The source text of an operator.
+const fn operator(kind: &TokenKind) -> &'static str {
+    match kind {
+        TokenKind::Bang => "!",
+        TokenKind::BangEqual => "!=",
+        TokenKind::EqualEqual => "==",
+        TokenKind::Greater => ">",
+        TokenKind::GreaterEqual => ">=",
+        TokenKind::Less => "<",
+        TokenKind::LessEqual => "<=",
+        TokenKind::Plus => "+",
+        TokenKind::Minus => "-",
+        TokenKind::Star => "*",
+        TokenKind::StarStar => "**",
+        TokenKind::Slash => "/",
+        TokenKind::SlashSlash => "//",
+        TokenKind::And => "and",
+        TokenKind::Or => "or",
+        _ => "?",
+    }
+}
@@ -1,15 +1,492 @@
-use crate::parser::ast::{Annotation, Expr, Statement};
-use crate::parser::{Parser, ParserError};
+use crate::parser::ast::{Expr, Param, Statement};
+use crate::parser::expression::starts_expression;
+use crate::parser::{ErrorKind, Parser, ParserError};
 use crate::span::{Span, Spanned};
 use crate::tokens::TokenKind;
 
 type StatementResult = Result<Spanned<Statement>, ParserError>;
 
-impl<'a> Parser<'a> {
+impl Parser<'_> {
     /// Parses a statement.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ParserError` if the tokens don't form a valid statement.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Annotation, Statement};
+    /// use alpaca::parser::{ErrorKind, Parser};
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let (statement, _) = Parser::new("let x: Int = 5", "example").parse_statement().unwrap();
+    /// let Statement::Let { annotation, .. } = statement else {
+    ///     panic!("expected a let statement");
+    /// };
+    /// assert_eq!(annotation.unwrap().0, Annotation::Single("Int".to_string()));
+    ///
+    /// let (statement, _) = Parser::new("let x = 5", "example").parse_statement().unwrap();
+    /// assert!(matches!(statement, Statement::Let { annotation: None, .. }));
+    ///
+    /// let error = Parser::new(": Int", "example").parse_statement().unwrap_err();
+    /// assert!(matches!(error.kind(), ErrorKind::Unexpected(TokenKind::Colon, _)));
+    /// ```
     pub fn parse_statement(&mut self) -> StatementResult {
-        let peeked = self.peek();
+        self.observe("statement", Self::parse_statement_unobserved)
+    }
+
+    fn parse_statement_unobserved(&mut self) -> StatementResult {
+        match self.peek()?.0 {
+            TokenKind::Fun | TokenKind::Pub => self.parse_function(),
+            TokenKind::Let => self.parse_let(),
+            TokenKind::Type => self.parse_type(),
+            TokenKind::Return => self.parse_return(),
+            TokenKind::Assert => self.parse_assert(),
+            TokenKind::Break => self.parse_break(),
+            TokenKind::Continue => Ok((Statement::Continue, self.advance()?.1)),
+            _ => {
+                let expr = self.parse_expression(1)?;
+                if self.peek()?.0 == TokenKind::Semicolon {
+                    let span = Span::from(expr.1.start..self.advance()?.1.end);
+                    return Ok((Statement::Semi(expr), span));
+                }
+
+                let span = expr.1;
+                Ok((Statement::Expression(expr), span))
+            }
+        }
+    }
+
+    /// Parses a `let` statement, whose span runs from the `let` through to
+    /// the end of its value.
+    ///
+    /// ```
+    /// use alpaca::parser::Parser;
+    /// use alpaca::span::Span;
+    ///
+    /// let (_, span) = Parser::new("let x = 10", "example").parse_statement().unwrap();
+    /// assert_eq!(span, Span::from(0..10));
+    ///
+    /// let (_, span) = Parser::new("return x + 1", "example").parse_statement().unwrap();
+    /// assert_eq!(span, Span::from(0..12));
+    /// ```
+    fn parse_let(&mut self) -> StatementResult {
+        let keyword = self.advance()?.1; // Consume the `let`
+
+        let name = self.parse_identifier("variable")?;
+        let annotation = if self.peek()?.0 == TokenKind::Colon {
+            self.advance()?;
+            Some(self.parse_annotation()?)
+        } else {
+            None
+        };
+
+        self.consume(&TokenKind::Equal)
+            .map_err(|e| e.with_help("Expected an `=` followed by a value.".to_string()))?;
+        let value = self.parse_expression(1)?;
+        let span = keyword.merge(value.1);
+
+        Ok((
+            Statement::Let {
+                name,
+                annotation,
+                value,
+            },
+            span,
+        ))
+    }
+
+    /// Parses a function declaration, attaching the doc comments written
+    /// directly before it.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::Statement;
+    /// use alpaca::parser::Parser;
+    ///
+    /// let source = "## Adds one.\n## Works on any Int.\nfun f(x) do x + 1 end";
+    /// let (statement, _) = Parser::new(source, "example").parse_statement().unwrap();
+    /// let Statement::Function { docs, .. } = statement else {
+    ///     panic!("expected a function");
+    /// };
+    /// assert_eq!(docs.as_deref(), Some("Adds one.\nWorks on any Int."));
+    /// ```
+    fn parse_function(&mut self) -> StatementResult {
+        let (
+            Statement::Signature {
+                name,
+                docs,
+                public,
+                params,
+                return_annotation,
+            },
+            span,
+        ) = self.parse_signature()?
+        else {
+            unreachable!("parse_signature always returns a signature.")
+        };
+
+        let body = self.parse_do_block(None, "A function's body starts with `do`.")?;
+        let span = Span::from(span.start..body.1.end);
+
+        Ok((
+            Statement::Function {
+                name,
+                docs,
+                public,
+                params,
+                return_annotation,
+                body,
+            },
+            span,
+        ))
+    }
+
+    /// Parses a statement in an interface file, which can only declare types
+    /// and function signatures.
+    pub(super) fn parse_interface_statement(&mut self) -> StatementResult {
+        let (token, span) = self.peek()?.clone();
+        match token {
+            TokenKind::Fun | TokenKind::Pub => {
+                let signature = self.parse_signature()?;
+                match self.peek()? {
+                    (TokenKind::Do, span) => Err(ParserError::new(
+                        ErrorKind::Other(
+                            "Functions in an interface file can't have a body.".to_string(),
+                            *span,
+                        ),
+                        Some("Remove the body, leaving just the signature.".to_string()),
+                    )),
+                    _ => Ok(signature),
+                }
+            }
+            TokenKind::Type => self.parse_type(),
+            token => {
+                // Skip the offending token, otherwise recovery would stop at
+                // it again and retry this statement forever.
+                self.advance()?;
+                Err(ParserError::new(
+                    ErrorKind::unexpected(token, span),
+                    Some(
+                        "Interface files can only contain type declarations and function \
+                         signatures."
+                            .to_string(),
+                    ),
+                ))
+            }
+        }
+    }
+
+    /// Parses a function up to its body, as a `Statement::Signature`.
+    fn parse_signature(&mut self) -> StatementResult {
+        // The `fun` (or `pub`) has only been peeked, so these are the doc
+        // comments written directly before it.
+        let docs = self.docs.take();
+
+        let (public, start) = match self.advance()? {
+            (TokenKind::Pub, span) => {
+                self.consume(&TokenKind::Fun)
+                    .map_err(|e| e.with_help("Expected a function after `pub`.".to_string()))?;
+                (true, span.start)
+            }
+            (_, span) => (false, span.start),
+        };
+
+        let name = self.parse_identifier("function")?;
+
+        self.consume(&TokenKind::OpenParen)
+            .map_err(|e| e.with_help("Expected a parameter list.".to_string()))?;
+
+        let mut params: Vec<Param> = Vec::new();
+        while self.peek()?.0 != TokenKind::CloseParen {
+            if let Some(variadic) = params.last().filter(|p| p.variadic) {
+                return Err(ParserError::new(
+                    ErrorKind::Other(
+                        format!("Variadic parameter `{}` isn't last.", variadic.name.0),
+                        variadic.name.1,
+                    ),
+                    Some("Move it to the end of the parameters.".to_string()),
+                )
+                .with_note("Only the final parameter can be variadic.".to_string()));
+            }
+
+            let param = self.parse_param(&params)?;
+            params.push(param);
+
+            // Consume a comma if we haven't reached the end of the parameters.
+            if self.peek()?.0 != TokenKind::CloseParen {
+                self.consume(&TokenKind::Comma)
+                    .map_err(|e| e.with_help("Did you forget a comma?".to_string()))?;
+            }
+        }
+        self.consume(&TokenKind::CloseParen)
+            .map_err(|e| e.with_help("Expeted to find a closing parenthesis.".to_string()))?;
+
+        let return_annotation = if self.peek()?.0 == TokenKind::Arrow {
+            self.advance()?;
+            Some(self.parse_annotation()?)
+        } else {
+            None
+        };
+
+        let span = Span::from(start..self.current_token_span.end);
+
+        Ok((
+            Statement::Signature {
+                name,
+                docs,
+                public,
+                params,
+                return_annotation,
+            },
+            span,
+        ))
+    }
+
+    /// Parses a single parameter of a function, given the ones before it.
+    ///
+    /// Parameters must have different names, so a repeated one is an error
+    /// pointing at the second.
+    ///
+    /// ```
+    /// use alpaca::parser::{ErrorKind, Parser};
+    /// use alpaca::span::Span;
+    ///
+    /// let error = Parser::new("fun f(x, x) do x end", "example")
+    ///     .parse_statement()
+    ///     .unwrap_err();
+    /// assert!(matches!(error.kind(), ErrorKind::Other(message, span)
+    ///     if message == "Duplicate parameter `x`." && *span == Span::from(9..10)));
+    ///
+    /// assert!(Parser::new("fun f(x, y) do x end", "example").parse_statement().is_ok());
+    /// ```
+    fn parse_param(&mut self, params: &[Param]) -> Result<Param, ParserError> {
+        let variadic = self.peek()?.0 == TokenKind::Ellipsis;
+        if variadic {
+            self.advance()?;
+        }
+        let mutable = self.peek()?.0 == TokenKind::Mut;
+        if mutable {
+            self.advance()?;
+        }
+        let by_ref = self.peek()?.0 == TokenKind::Ref;
+        if by_ref {
+            self.advance()?;
+        }
+
+        let (Expr::Ident(name), span) = self.parse_identifier("parameter")? else {
+            unreachable!("parse_identifier always returns an identifier.")
+        };
+        if params.iter().any(|p| p.name.0 == name) {
+            return Err(ParserError::new(
+                ErrorKind::Other(format!("Duplicate parameter `{name}`."), span),
+                Some("Each parameter needs a different name.".to_string()),
+            ));
+        }
+
+        let annotation = if self.peek()?.0 == TokenKind::Colon {
+            self.advance()?;
+            Some(self.parse_annotation()?)
+        } else {
+            None
+        };
+
+        let default = if self.peek()?.0 == TokenKind::Equal {
+            self.advance()?;
+            Some(self.parse_expression(1)?)
+        } else {
+            None
+        };
+        if variadic {
+            if let Some(default) = &default {
+                return Err(ParserError::new(
+                    ErrorKind::Other(
+                        "A variadic parameter can't have a default.".to_string(),
+                        default.1,
+                    ),
+                    Some("Remove the default value.".to_string()),
+                ));
+            }
+        } else if default.is_none() {
+            if let Some(defaulted) = params.iter().find(|p| p.default.is_some()) {
+                return Err(ParserError::new(
+                    ErrorKind::Other(format!("Parameter `{name}` needs a default value."), span),
+                    Some(format!(
+                        "Give it a default, or move it before `{}`.",
+                        defaulted.name.0
+                    )),
+                )
+                .with_note("Only the trailing parameters can have defaults.".to_string()));
+            }
+        }
+
+        Ok(Param {
+            name: (name, span),
+            mutable,
+            by_ref,
+            annotation,
+            default,
+            variadic,
+        })
+    }
+
+    /// Parses a `type` statement, which is a record definition if a `{`
+    /// follows the `=`, and an alias otherwise.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Annotation, Statement};
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (statement, _) = Parser::new("type Name = String", "example").parse_statement().unwrap();
+    /// let Statement::TypeAlias { target, .. } = statement else {
+    ///     panic!("expected an alias");
+    /// };
+    /// assert_eq!(target.0, Annotation::Single("String".to_string()));
+    ///
+    /// let (statement, _) = Parser::new("type Pair = (Int, Int)", "example").parse_statement().unwrap();
+    /// assert!(matches!(statement, Statement::TypeAlias { .. }));
+    ///
+    /// let source = "type Point = { x: Int, y: Int }";
+    /// let (statement, _) = Parser::new(source, "example").parse_statement().unwrap();
+    /// let Statement::Record { fields, .. } = statement else {
+    ///     panic!("expected a record");
+    /// };
+    /// let names: Vec<&str> = fields.iter().map(|((name, _), _)| name.as_str()).collect();
+    /// assert_eq!(names, ["x", "y"]);
+    /// ```
+    fn parse_type(&mut self) -> StatementResult {
+        let start = self.advance()?.1.start; // Consume the `type`
+
+        let name = self.parse_identifier("type")?;
+        self.consume(&TokenKind::Equal)
+            .map_err(|e| e.with_help("Expected an `=` followed by a type.".to_string()))?;
+
+        // A brace after the `=` starts a record, anything else is an alias.
+        if self.peek()?.0 != TokenKind::OpenBrace {
+            let target = self.parse_annotation()?;
+            let span = Span::from(start..target.1.end);
+            return Ok((Statement::TypeAlias { name, target }, span));
+        }
+
+        self.advance()?; // Consume the `{`
+        let mut fields = Vec::new();
+        while self.peek()?.0 != TokenKind::CloseBrace {
+            let (Expr::Ident(field), field_span) = self.parse_identifier("field")? else {
+                unreachable!("parse_identifier always returns an identifier.")
+            };
+            self.consume(&TokenKind::Colon).map_err(|e| {
+                e.with_help("Expected a `:` followed by the field's type.".to_string())
+            })?;
+            fields.push(((field, field_span), self.parse_annotation()?));
+
+            // Consume a comma if we haven't reached the end of the fields.
+            if self.peek()?.0 != TokenKind::CloseBrace {
+                self.consume(&TokenKind::Comma)
+                    .map_err(|e| e.with_help("Did you forget a comma?".to_string()))?;
+            }
+        }
+        self.consume(&TokenKind::CloseBrace)?;
+
+        let span = Span::from(start..self.current_token_span.end);
+        Ok((Statement::Record { name, fields }, span))
+    }
+
+    fn parse_return(&mut self) -> StatementResult {
+        let keyword = self.advance()?.1; // Consume the `return`
+
+        let value = self.parse_expression(1)?;
+        let span = keyword.merge(value.1);
+        Ok((Statement::Return(value), span))
+    }
+
+    fn parse_assert(&mut self) -> StatementResult {
+        let start = self.advance()?.1.start; // Consume the `assert`
+
+        let cond = self.parse_expression(1)?;
+        let message = if self.peek()?.0 == TokenKind::Comma {
+            self.advance()?;
+            Some(self.parse_expression(1)?)
+        } else {
+            None
+        };
+
+        let end = message.as_ref().map_or(cond.1.end, |m| m.1.end);
+        Ok((Statement::Assert { cond, message }, Span::from(start..end)))
+    }
+
+    /// Parses a `break`, with an optional label and value. The value has to
+    /// start on the same line as the `break`, so an expression on the next
+    /// line is a statement of its own.
+    ///
+    /// ```
+    /// use alpaca::parser::ast::{Expr, LiteralKind, Statement};
+    /// use alpaca::parser::Parser;
+    ///
+    /// let (statement, _) = Parser::new("break 5", "example").parse_statement().unwrap();
+    /// let Statement::Break { value, .. } = statement else {
+    ///     panic!("expected a break");
+    /// };
+    /// assert_eq!(value.unwrap().0, Expr::Literal(LiteralKind::Int(5, None)));
+    ///
+    /// let (statement, _) = Parser::new("break", "example").parse_statement().unwrap();
+    /// assert!(matches!(statement, Statement::Break { value: None, .. }));
+    ///
+    /// let statements = Parser::new("break\nfoo()", "example").parse().unwrap();
+    /// assert!(matches!(statements[0].0, Statement::Break { value: None, .. }));
+    /// assert_eq!(statements.len(), 2);
+    /// ```
+    fn parse_break(&mut self) -> StatementResult {
+        let mut span = self.advance()?.1; // Consume the `break`
+
+        let label = if let TokenKind::Label(label) = &self.peek()?.0 {
+            let label = label.to_string();
+            span.end = self.advance()?.1.end;
+            Some((label, self.current_token_span))
+        } else {
+            None
+        };
+
+        // Anything on the same line which can start an expression is the
+        // value being broken with.
+        let value = if starts_expression(&self.peek()?.0) && self.next_on_same_line()? {
+            let value = self.parse_expression(1)?;
+            span.end = value.1.end;
+            Some(value)
+        } else {
+            None
+        };
+
+        Ok((Statement::Break { label, value }, span))
+    }
 
-        todo!()
+    /// Parses the name of something, described by `what` (such as
+    /// `"variable"`) if a keyword is written in its place.
+    ///
+    /// ```
+    /// use alpaca::parser::Parser;
+    ///
+    /// let errors = Parser::new("let fun = 3", "example").parse().unwrap_err();
+    /// assert_eq!(
+    ///     errors[0].to_string(),
+    ///     "error: `fun` is a reserved keyword and can't be used as a variable name\n  \
+    ///      help: Pick another name, such as `fun_`."
+    /// );
+    /// ```
+    pub(super) fn parse_identifier(
+        &mut self,
+        what: &'static str,
+    ) -> Result<Spanned<Expr>, ParserError> {
+        match self.advance()? {
+            (TokenKind::Ident(name), span) => Ok((Expr::Ident(name.into_owned()), span)),
+            (keyword, span) if keyword.is_keyword() => {
+                let help = format!("Pick another name, such as `{keyword}_`.");
+                Err(ParserError::new(
+                    ErrorKind::Reserved(keyword.into_owned(), what, span),
+                    Some(help),
+                ))
+            }
+            (token, span) => Err(ParserError::new(
+                ErrorKind::ExpectedItem("identifier", token.into_owned(), span),
+                None,
+            )),
+        }
     }
 }
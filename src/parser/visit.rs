@@ -0,0 +1,231 @@
+//! The `Visitor` trait, for passes which inspect the AST without changing it.
+//!
+//! This is the read-only counterpart to `Fold`. The default methods walk
+//! into every child of a node, so a pass only overrides the nodes it's
+//! interested in.
+//!
+//! `PathVisitor` is a variant which is also told the ancestors of each
+//! expression, for rules which depend on where an expression appears.
+
+use crate::parser::ast::{Expr, Statement};
+use crate::span::Spanned;
+
+/// Walks an AST node by node.
+///
+/// Overriding methods can call the free `walk_*` function for the node to
+/// carry on into its children.
+///
+/// The `'ast` lifetime lets a visitor hold on to the nodes it's given.
+pub trait Visitor<'ast> {
+    fn visit_statement(&mut self, statement: &'ast Spanned<Statement>) {
+        walk_statement(self, &statement.0);
+    }
+
+    fn visit_expr(&mut self, expr: &'ast Spanned<Expr>) {
+        walk_expr(self, &expr.0);
+    }
+}
+
+/// Visits each of the given statements.
+pub fn walk_statements<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    statements: &'ast [Spanned<Statement>],
+) {
+    for statement in statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+/// Visits the children of a statement.
+pub fn walk_statement<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    statement: &'ast Statement,
+) {
+    match statement {
+        Statement::Expression(expr) | Statement::Semi(expr) | Statement::Return(expr) => {
+            visitor.visit_expr(expr);
+        }
+        Statement::Assert { cond, message } => {
+            visitor.visit_expr(cond);
+            if let Some(message) = message {
+                visitor.visit_expr(message);
+            }
+        }
+        Statement::Break { value, .. } => {
+            if let Some(value) = value {
+                visitor.visit_expr(value);
+            }
+        }
+        Statement::Continue => {}
+        Statement::Let { name, value, .. } => {
+            visitor.visit_expr(name);
+            visitor.visit_expr(value);
+        }
+        Statement::Function {
+            name, params, body, ..
+        } => {
+            visitor.visit_expr(name);
+            for default in params.iter().filter_map(|p| p.default.as_ref()) {
+                visitor.visit_expr(default);
+            }
+            visitor.visit_expr(body);
+        }
+        Statement::Signature { name, params, .. } => {
+            visitor.visit_expr(name);
+            for default in params.iter().filter_map(|p| p.default.as_ref()) {
+                visitor.visit_expr(default);
+            }
+        }
+        Statement::TypeAlias { name, .. } | Statement::Record { name, .. } => {
+            visitor.visit_expr(name);
+        }
+    }
+}
+
+/// Visits the children of an expression.
+pub fn walk_expr<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, expr: &'ast Expr) {
+    match expr {
+        Expr::Literal(_) | Expr::Ident(_) | Expr::Path(_) => {}
+        Expr::Grouping(expr) | Expr::Spread(expr) => visitor.visit_expr(expr),
+        Expr::Tuple(items) | Expr::Array(items) => {
+            for item in items {
+                visitor.visit_expr(item);
+            }
+        }
+        Expr::Map { entries } => {
+            for (key, value) in entries {
+                visitor.visit_expr(key);
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::Unary { rhs, .. } => visitor.visit_expr(rhs),
+        Expr::Binary { lhs, rhs, .. } | Expr::Logical { lhs, rhs, .. } => {
+            visitor.visit_expr(lhs);
+            visitor.visit_expr(rhs);
+        }
+        Expr::Call { callee, args } => {
+            visitor.visit_expr(callee);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        Expr::Field { target, .. } => visitor.visit_expr(target),
+        Expr::Assignment { name, value } => {
+            visitor.visit_expr(name);
+            visitor.visit_expr(value);
+        }
+        Expr::Block {
+            statements, tail, ..
+        } => {
+            walk_statements(visitor, statements);
+            if let Some(tail) = tail {
+                visitor.visit_expr(tail);
+            }
+        }
+        Expr::If {
+            condition,
+            body,
+            else_,
+        } => {
+            visitor.visit_expr(condition);
+            visitor.visit_expr(body);
+            if let Some(else_) = else_.as_ref() {
+                visitor.visit_expr(else_);
+            }
+        }
+        Expr::For { var, iter, body } => {
+            visitor.visit_expr(var);
+            visitor.visit_expr(iter);
+            visitor.visit_expr(body);
+        }
+        Expr::Where { body, bindings } => {
+            visitor.visit_expr(body);
+            for (name, value) in bindings {
+                visitor.visit_expr(name);
+                visitor.visit_expr(value);
+            }
+        }
+        Expr::While { expr, body } => {
+            visitor.visit_expr(expr);
+            visitor.visit_expr(body);
+        }
+        Expr::Match { expr, arms } => {
+            visitor.visit_expr(expr);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    visitor.visit_expr(guard);
+                }
+                visitor.visit_expr(&arm.body);
+            }
+        }
+    }
+}
+
+/// A visitor which is also given the ancestors of each expression it visits,
+/// from the outermost inwards. Statements aren't included, only the
+/// expressions containing them.
+///
+/// ```
+/// use alpaca::parser::ast::Expr;
+/// use alpaca::parser::visit::{walk_statements_with_path, PathVisitor};
+/// use alpaca::parser::Parser;
+/// use alpaca::span::Spanned;
+///
+/// struct LiteralAncestry(Vec<&'static str>);
+///
+/// impl PathVisitor<'_> for LiteralAncestry {
+///     fn visit_expr(&mut self, expr: &Spanned<Expr>, ancestors: &[&Expr]) {
+///         if let Expr::Literal(_) = expr.0 {
+///             self.0 = ancestors
+///                 .iter()
+///                 .map(|e| match e {
+///                     Expr::While { .. } => "while",
+///                     Expr::Block { .. } => "block",
+///                     Expr::Assignment { .. } => "assignment",
+///                     Expr::Binary { .. } => "binary",
+///                     _ => "other",
+///                 })
+///                 .collect();
+///         }
+///     }
+/// }
+///
+/// let statements = Parser::new("while x do x = x + 1 end", "example").parse().unwrap();
+/// let mut visitor = LiteralAncestry(Vec::new());
+/// walk_statements_with_path(&mut visitor, &statements);
+/// assert_eq!(visitor.0, ["while", "block", "assignment", "binary"]);
+/// ```
+pub trait PathVisitor<'ast> {
+    fn visit_expr(&mut self, expr: &'ast Spanned<Expr>, ancestors: &[&'ast Expr]);
+}
+
+/// Adapts a `PathVisitor` into a `Visitor`, keeping track of the ancestors
+/// of the expression being visited.
+struct WithPath<'ast, 'v, V: ?Sized> {
+    visitor: &'v mut V,
+    ancestors: Vec<&'ast Expr>,
+}
+
+impl<'ast, V: PathVisitor<'ast> + ?Sized> Visitor<'ast> for WithPath<'ast, '_, V> {
+    fn visit_expr(&mut self, expr: &'ast Spanned<Expr>) {
+        self.visitor.visit_expr(expr, &self.ancestors);
+        self.ancestors.push(&expr.0);
+        walk_expr(self, &expr.0);
+        self.ancestors.pop();
+    }
+}
+
+/// Visits every expression in the given statements, along with its
+/// ancestors.
+pub fn walk_statements_with_path<'ast, V: PathVisitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    statements: &'ast [Spanned<Statement>],
+) {
+    walk_statements(
+        &mut WithPath {
+            visitor,
+            ancestors: Vec::new(),
+        },
+        statements,
+    );
+}
@@ -1,16 +1,188 @@
 //! Contains the `Span` struct and `Spanned` type for representing positions
 //! of items in alpaca throughout the source code.
 
-use std::ops::Range;
+use alloc::{format, string::String};
+use core::ops::Range;
+
+use crate::lexer::is_line_terminator;
 
 pub type Spanned<T> = (T, Span);
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+/// Transforms the value of a `Spanned`, keeping its span.
+///
+/// ```
+/// use alpaca::span::{map_spanned, Span};
+///
+/// let spanned = (2, Span::from(0..1));
+/// assert_eq!(map_spanned(spanned, |n| n * 10), (20, Span::from(0..1)));
+/// ```
+pub fn map_spanned<T, U>(spanned: Spanned<T>, f: impl FnOnce(T) -> U) -> Spanned<U> {
+    (f(spanned.0), spanned.1)
+}
+
+/// Borrows the value of a `Spanned`, alongside its span.
+pub const fn spanned_ref<T>(spanned: &Spanned<T>) -> (&T, Span) {
+    (&spanned.0, spanned.1)
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct Span {
     pub start: usize,
     pub end: usize,
 }
 
+impl Span {
+    /// Checks whether the byte `offset` falls within the span.
+    #[must_use]
+    pub const fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+
+    /// The smallest span covering both spans, and anything between them.
+    ///
+    /// ```
+    /// use alpaca::span::Span;
+    ///
+    /// assert_eq!(Span::from(0..3).merge(Span::from(8..10)), Span::from(0..10));
+    /// assert_eq!(Span::from(8..10).merge(Span::from(0..3)), Span::from(0..10));
+    /// ```
+    #[must_use]
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+
+    /// Formats where the span starts as `filename:line:column`, the usual
+    /// prefix for a line of compiler output.
+    ///
+    /// ```
+    /// use alpaca::span::Span;
+    ///
+    /// let source = "let x = 1\nlet y = 2\nlet z = x + y";
+    /// let span = Span::from(24..25);
+    /// assert_eq!(span.display_location(source, "foo.alp"), "foo.alp:3:5");
+    /// ```
+    #[must_use]
+    pub fn display_location(&self, source: &str, filename: &str) -> String {
+        let (line, column) = line_col(source, self.start);
+        format!("{filename}:{line}:{column}")
+    }
+}
+
+/// Finds the line and column of the byte `offset` in `source`, both counting
+/// from 1. Columns count characters rather than bytes.
+///
+/// Lines end wherever the lexer ends them, so the line numbers agree with
+/// `Lexer::line`.
+///
+/// ```
+/// use alpaca::span::line_col;
+///
+/// assert_eq!(line_col("let x = 1\nlet y = 2", 14), (2, 5));
+/// assert_eq!(line_col("let x = 1\u{2028}let y = 2", 16), (2, 5));
+/// assert_eq!(line_col("let x = 1\r\nlet y = 2", 15), (2, 5));
+/// ```
+#[must_use]
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let (line, line_start) = line_breaks(before);
+
+    let column = before[line_start..].chars().count() + 1;
+    (line + 1, column)
+}
+
+/// Counts the line breaks in `text`, returning the count and the byte
+/// offset the last line starts at. `\r\n` is a single line break.
+fn line_breaks(text: &str) -> (usize, usize) {
+    let mut count = 0;
+    let mut line_start = 0;
+
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if is_line_terminator(c) && !(c == '\r' && matches!(chars.peek(), Some((_, '\n')))) {
+            count += 1;
+            line_start = i + c.len_utf8();
+        }
+    }
+
+    (count, line_start)
+}
+
+#[cfg(feature = "lsp")]
+impl Span {
+    /// Converts the span into the range of `source` a language server would
+    /// report. Lines count from 0, and characters count UTF-16 code units.
+    ///
+    /// ```
+    /// use alpaca::span::Span;
+    /// use lsp_types::{Position, Range};
+    ///
+    /// let source = "let x = 1\nlet y = 2";
+    /// assert_eq!(
+    ///     Span::from(14..15).to_lsp_range(source),
+    ///     Range::new(Position::new(1, 4), Position::new(1, 5))
+    /// );
+    ///
+    /// // Lines end wherever the lexer ends them.
+    /// let source = "let x = 1\u{2028}let y = 2";
+    /// assert_eq!(Span::from(16..17).to_lsp_range(source).start, Position::new(1, 4));
+    /// ```
+    #[must_use]
+    pub fn to_lsp_range(&self, source: &str) -> lsp_types::Range {
+        lsp_types::Range::new(
+            lsp_position(source, self.start),
+            lsp_position(source, self.end),
+        )
+    }
+
+    /// Pairs the span with the source it points into, so it can be converted
+    /// into an `lsp_types::Range` with `From`.
+    ///
+    /// ```
+    /// use alpaca::span::Span;
+    /// use lsp_types::{Position, Range};
+    ///
+    /// let source = "let x = 1\nlet y = 2";
+    /// let range = Range::from(Span::from(14..15).with_source(source));
+    /// assert_eq!(range, Range::new(Position::new(1, 4), Position::new(1, 5)));
+    /// ```
+    #[must_use]
+    pub const fn with_source(self, source: &str) -> SourceSpan<'_> {
+        SourceSpan { span: self, source }
+    }
+}
+
+/// A span along with the source it points into, which is what's needed to
+/// find its lines and characters.
+#[cfg(feature = "lsp")]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct SourceSpan<'a> {
+    pub span: Span,
+    pub source: &'a str,
+}
+
+#[cfg(feature = "lsp")]
+impl From<SourceSpan<'_>> for lsp_types::Range {
+    fn from(value: SourceSpan<'_>) -> Self {
+        value.span.to_lsp_range(value.source)
+    }
+}
+
+/// Finds the LSP position of the byte `offset` in `source`.
+#[cfg(feature = "lsp")]
+fn lsp_position(source: &str, offset: usize) -> lsp_types::Position {
+    let before = &source[..offset.min(source.len())];
+    let (line, line_start) = line_breaks(before);
+
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    lsp_types::Position::new(
+        u32::try_from(line).unwrap_or(u32::MAX),
+        u32::try_from(character).unwrap_or(u32::MAX),
+    )
+}
+
 impl From<Range<usize>> for Span {
     fn from(value: Range<usize>) -> Self {
         Self {
@@ -1,14 +1,34 @@
+//! Contains the `TokenKind` enum, representing every token the lexer
+//! can produce.
+
+use alloc::{borrow::Cow, string::String};
+use core::fmt;
+
 /// Every token in Alpaca.
-#[derive(Debug, PartialEq, Clone)]
-pub enum TokenKind {
+///
+/// Identifiers, integers, and strings borrow their contents from the source
+/// where possible, and only allocate when the lexer has to build a new value.
+///
+/// New tokens are added as the language grows, so matches outside this crate
+/// need a wildcard arm.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[non_exhaustive]
+pub enum TokenKind<'a> {
     // Punctuation
     OpenParen,
     CloseParen,
     OpenBracket,
     CloseBracket,
+    OpenBrace,
+    CloseBrace,
     Comma,
+    Semicolon,
     Dot,
+    DotDot,
+    DotDotEqual,
+    Ellipsis,
     Colon,
+    ColonColon,
     Arrow,
 
     // Operators
@@ -18,22 +38,36 @@ pub enum TokenKind {
     BangEqual,
     Greater,
     GreaterEqual,
+    GreaterGreater,
     Less,
     LessEqual,
+    LessLess,
     Plus,
     Minus,
     Star,
+    StarStar,
     Slash,
+    SlashSlash,
+    Pipe,
+    Question,
+    /// A custom operator, such as `<+>`, made of the characters
+    /// `+-*/<>=!&|^` but not one of the operators above.
+    Operator(Cow<'a, str>),
 
     // Literals
-    String(String),
-    Integer(String),
+    String(Cow<'a, str>),
+    Integer(Cow<'a, str>),
 
     // Identifiers
-    Ident(String),
+    Ident(Cow<'a, str>),
+    /// A block label, `'name`. Holds the name without the leading quote.
+    Label(Cow<'a, str>),
 
     // Keywords
     And,
+    Assert,
+    Break,
+    Continue,
     Do,
     Else,
     End,
@@ -42,13 +76,312 @@ pub enum TokenKind {
     Fun,
     If,
     Let,
+    Match,
+    Mut,
     Or,
+    Pub,
+    Ref,
     Return,
     True,
     Type,
+    Where,
     While,
 
     // Misc
+    /// A doc comment, `## text` or `/** text */`. Holds the text after the
+    /// `##`, or between the delimiters.
+    DocComment(Cow<'a, str>),
+    /// An ordinary comment, `# text` or `/* text */`, only lexed as a token
+    /// when trivia is being preserved. Holds the text after the `#`, or
+    /// between the delimiters.
+    Comment(Cow<'a, str>),
     Error(String),
     EoF,
 }
+
+/// What kind of thing a token is, for tools such as syntax highlighters to
+/// pick a color by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenCategory {
+    Keyword,
+    Operator,
+    Literal,
+    Identifier,
+    Punctuation,
+    Comment,
+    Error,
+}
+
+impl TokenKind<'_> {
+    /// The category this token belongs to.
+    ///
+    /// ```
+    /// use alpaca::tokens::{TokenCategory, TokenKind};
+    ///
+    /// assert_eq!(TokenKind::While.category(), TokenCategory::Keyword);
+    /// assert_eq!(TokenKind::Plus.category(), TokenCategory::Operator);
+    /// assert_eq!(TokenKind::String("hi".into()).category(), TokenCategory::Literal);
+    /// ```
+    #[must_use]
+    pub const fn category(&self) -> TokenCategory {
+        match self {
+            Self::String(_) | Self::Integer(_) | Self::True | Self::False => TokenCategory::Literal,
+            Self::Ident(_) | Self::Label(_) => TokenCategory::Identifier,
+            Self::DocComment(_) | Self::Comment(_) => TokenCategory::Comment,
+            Self::Error(_) => TokenCategory::Error,
+            Self::And
+            | Self::Assert
+            | Self::Break
+            | Self::Continue
+            | Self::Do
+            | Self::Else
+            | Self::End
+            | Self::For
+            | Self::Fun
+            | Self::If
+            | Self::Let
+            | Self::Match
+            | Self::Mut
+            | Self::Or
+            | Self::Pub
+            | Self::Ref
+            | Self::Return
+            | Self::Type
+            | Self::Where
+            | Self::While => TokenCategory::Keyword,
+            Self::DotDot
+            | Self::DotDotEqual
+            | Self::Ellipsis
+            | Self::Equal
+            | Self::EqualEqual
+            | Self::Bang
+            | Self::BangEqual
+            | Self::Greater
+            | Self::GreaterEqual
+            | Self::GreaterGreater
+            | Self::Less
+            | Self::LessEqual
+            | Self::LessLess
+            | Self::Plus
+            | Self::Minus
+            | Self::Star
+            | Self::StarStar
+            | Self::Slash
+            | Self::SlashSlash
+            | Self::Pipe
+            | Self::Question
+            | Self::Operator(_) => TokenCategory::Operator,
+            // The end of the file has no text to color, so any category
+            // would do.
+            Self::OpenParen
+            | Self::CloseParen
+            | Self::OpenBracket
+            | Self::CloseBracket
+            | Self::OpenBrace
+            | Self::CloseBrace
+            | Self::Comma
+            | Self::Semicolon
+            | Self::Dot
+            | Self::Colon
+            | Self::ColonColon
+            | Self::Arrow
+            | Self::EoF => TokenCategory::Punctuation,
+        }
+    }
+
+    /// Whether this token is a reserved keyword, which can't be used as a
+    /// name.
+    #[must_use]
+    pub const fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            Self::And
+                | Self::Assert
+                | Self::Break
+                | Self::Continue
+                | Self::Do
+                | Self::Else
+                | Self::End
+                | Self::False
+                | Self::For
+                | Self::Fun
+                | Self::If
+                | Self::Let
+                | Self::Match
+                | Self::Mut
+                | Self::Or
+                | Self::Pub
+                | Self::Ref
+                | Self::Return
+                | Self::True
+                | Self::Type
+                | Self::Where
+                | Self::While
+        )
+    }
+
+    /// Converts this token into one which owns all of its data, so it can
+    /// outlive the source string it was lexed from.
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    ///
+    /// use alpaca::lexer::Lexer;
+    /// use alpaca::tokens::TokenKind;
+    ///
+    /// let owned: TokenKind<'static> = {
+    ///     let source = String::from("name");
+    ///     let (borrowed, _) = Lexer::new(&source).next_token();
+    ///     assert!(matches!(borrowed, TokenKind::Ident(Cow::Borrowed(_))));
+    ///
+    ///     let owned = borrowed.clone().into_owned();
+    ///     assert!(matches!(owned, TokenKind::Ident(Cow::Owned(_))));
+    ///     assert_eq!(owned, borrowed);
+    ///     owned
+    /// };
+    ///
+    /// // The source has been dropped, but the owned token is still usable.
+    /// assert_eq!(owned, TokenKind::Ident("name".into()));
+    /// ```
+    #[must_use]
+    pub fn into_owned(self) -> TokenKind<'static> {
+        match self {
+            Self::OpenParen => TokenKind::OpenParen,
+            Self::CloseParen => TokenKind::CloseParen,
+            Self::OpenBracket => TokenKind::OpenBracket,
+            Self::CloseBracket => TokenKind::CloseBracket,
+            Self::OpenBrace => TokenKind::OpenBrace,
+            Self::CloseBrace => TokenKind::CloseBrace,
+            Self::Comma => TokenKind::Comma,
+            Self::Semicolon => TokenKind::Semicolon,
+            Self::Dot => TokenKind::Dot,
+            Self::DotDot => TokenKind::DotDot,
+            Self::DotDotEqual => TokenKind::DotDotEqual,
+            Self::Ellipsis => TokenKind::Ellipsis,
+            Self::Colon => TokenKind::Colon,
+            Self::ColonColon => TokenKind::ColonColon,
+            Self::Arrow => TokenKind::Arrow,
+            Self::Equal => TokenKind::Equal,
+            Self::EqualEqual => TokenKind::EqualEqual,
+            Self::Bang => TokenKind::Bang,
+            Self::BangEqual => TokenKind::BangEqual,
+            Self::Greater => TokenKind::Greater,
+            Self::GreaterEqual => TokenKind::GreaterEqual,
+            Self::GreaterGreater => TokenKind::GreaterGreater,
+            Self::Less => TokenKind::Less,
+            Self::LessEqual => TokenKind::LessEqual,
+            Self::LessLess => TokenKind::LessLess,
+            Self::Plus => TokenKind::Plus,
+            Self::Minus => TokenKind::Minus,
+            Self::Star => TokenKind::Star,
+            Self::StarStar => TokenKind::StarStar,
+            Self::Slash => TokenKind::Slash,
+            Self::SlashSlash => TokenKind::SlashSlash,
+            Self::Pipe => TokenKind::Pipe,
+            Self::Question => TokenKind::Question,
+            Self::Operator(s) => TokenKind::Operator(Cow::Owned(s.into_owned())),
+            Self::String(s) => TokenKind::String(Cow::Owned(s.into_owned())),
+            Self::Integer(s) => TokenKind::Integer(Cow::Owned(s.into_owned())),
+            Self::Ident(s) => TokenKind::Ident(Cow::Owned(s.into_owned())),
+            Self::Label(s) => TokenKind::Label(Cow::Owned(s.into_owned())),
+            Self::And => TokenKind::And,
+            Self::Assert => TokenKind::Assert,
+            Self::Break => TokenKind::Break,
+            Self::Continue => TokenKind::Continue,
+            Self::Do => TokenKind::Do,
+            Self::Else => TokenKind::Else,
+            Self::End => TokenKind::End,
+            Self::False => TokenKind::False,
+            Self::For => TokenKind::For,
+            Self::Fun => TokenKind::Fun,
+            Self::If => TokenKind::If,
+            Self::Let => TokenKind::Let,
+            Self::Match => TokenKind::Match,
+            Self::Mut => TokenKind::Mut,
+            Self::Or => TokenKind::Or,
+            Self::Pub => TokenKind::Pub,
+            Self::Ref => TokenKind::Ref,
+            Self::Return => TokenKind::Return,
+            Self::True => TokenKind::True,
+            Self::Type => TokenKind::Type,
+            Self::Where => TokenKind::Where,
+            Self::While => TokenKind::While,
+            Self::DocComment(s) => TokenKind::DocComment(Cow::Owned(s.into_owned())),
+            Self::Comment(s) => TokenKind::Comment(Cow::Owned(s.into_owned())),
+            Self::Error(s) => TokenKind::Error(s),
+            Self::EoF => TokenKind::EoF,
+        }
+    }
+}
+
+/// Displays a token as it's written in the source.
+impl fmt::Display for TokenKind<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::OpenParen => "(",
+            Self::CloseParen => ")",
+            Self::OpenBracket => "[",
+            Self::CloseBracket => "]",
+            Self::OpenBrace => "{",
+            Self::CloseBrace => "}",
+            Self::Comma => ",",
+            Self::Semicolon => ";",
+            Self::Dot => ".",
+            Self::DotDot => "..",
+            Self::DotDotEqual => "..=",
+            Self::Ellipsis => "...",
+            Self::Colon => ":",
+            Self::ColonColon => "::",
+            Self::Arrow => "->",
+            Self::Equal => "=",
+            Self::EqualEqual => "==",
+            Self::Bang => "!",
+            Self::BangEqual => "!=",
+            Self::Greater => ">",
+            Self::GreaterEqual => ">=",
+            Self::GreaterGreater => ">>",
+            Self::Less => "<",
+            Self::LessEqual => "<=",
+            Self::LessLess => "<<",
+            Self::Plus => "+",
+            Self::Minus => "-",
+            Self::Star => "*",
+            Self::StarStar => "**",
+            Self::Slash => "/",
+            Self::SlashSlash => "//",
+            Self::Pipe => "|>",
+            Self::Question => "?",
+            Self::String(s) => return write!(f, "\"{s}\""),
+            Self::Integer(s) | Self::Ident(s) | Self::Operator(s) => s,
+            Self::Label(s) => return write!(f, "'{s}"),
+            Self::And => "and",
+            Self::Assert => "assert",
+            Self::Break => "break",
+            Self::Continue => "continue",
+            Self::Do => "do",
+            Self::Else => "else",
+            Self::End => "end",
+            Self::False => "false",
+            Self::For => "for",
+            Self::Fun => "fun",
+            Self::If => "if",
+            Self::Let => "let",
+            Self::Match => "match",
+            Self::Mut => "mut",
+            Self::Or => "or",
+            Self::Pub => "pub",
+            Self::Ref => "ref",
+            Self::Return => "return",
+            Self::True => "true",
+            Self::Type => "type",
+            Self::Where => "where",
+            Self::While => "while",
+            Self::DocComment(s) => return write!(f, "##{s}"),
+            Self::Comment(s) => return write!(f, "#{s}"),
+            Self::Error(message) => message,
+            Self::EoF => "end of file",
+        };
+
+        f.write_str(text)
+    }
+}
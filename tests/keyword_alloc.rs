@@ -0,0 +1,55 @@
+//! Checks that lexing identifiers and keywords doesn't allocate.
+//!
+//! This installs its own global allocator, so it lives in a test binary of
+//! its own.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Cow;
+use std::cell::Cell;
+
+use alpaca::lexer::Lexer;
+use alpaca::tokens::TokenKind;
+
+thread_local! {
+    // Counted per thread, so allocations made by the test harness on other
+    // threads aren't counted.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts every allocation made on the current thread.
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+#[test]
+fn identifiers_and_keywords_do_not_allocate() {
+    let mut lexer = Lexer::new("let name while");
+
+    let before = allocations();
+    let tokens = [
+        lexer.next_token().0,
+        lexer.next_token().0,
+        lexer.next_token().0,
+    ];
+    assert_eq!(allocations(), before);
+
+    assert_eq!(tokens[0], TokenKind::Let);
+    assert!(matches!(tokens[1], TokenKind::Ident(Cow::Borrowed("name"))));
+    assert_eq!(tokens[2], TokenKind::While);
+}
@@ -0,0 +1,12 @@
+//! Checks that `TokenKind` stays `#[non_exhaustive]` for crates using it.
+//!
+//! The failing case lists every variant, so its expected error only matches
+//! while a `_` arm is the one thing missing. A new variant changes the error,
+//! and the list has to be updated along with it.
+
+#[test]
+fn token_kind_needs_a_wildcard_arm() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/token_kind_wildcard.rs");
+    cases.compile_fail("tests/ui/token_kind_exhaustive.rs");
+}
@@ -0,0 +1,62 @@
+//! Checks how expressions group, by comparing each one's s-expression
+//! against the expected grouping.
+
+use alpaca::parser::sexpr::to_sexpr;
+use alpaca::parser::Parser;
+
+/// Each source, along with the s-expression it should parse into.
+const CASES: &[(&str, &str)] = &[
+    // Arithmetic
+    ("1 + 2 * 3", "(+ 1 (* 2 3))"),
+    ("1 * 2 + 3", "(+ (* 1 2) 3)"),
+    ("a - b - c", "(- (- a b) c)"),
+    ("a - b + c", "(+ (- a b) c)"),
+    ("a / b / c", "(/ (/ a b) c)"),
+    ("a // b * c", "(* (// a b) c)"),
+    ("(a + b) * c", "(* (+ a b) c)"),
+    // Exponentiation
+    ("a ** b ** c", "(** a (** b c))"),
+    ("a * b ** c", "(* a (** b c))"),
+    ("-a ** b", "(- (** a b))"),
+    ("a ** -b", "(** a (- b))"),
+    ("!a ** b", "(! (** a b))"),
+    // Comparison and equality
+    ("a < b == c", "(== (< a b) c)"),
+    ("a == b < c", "(== a (< b c))"),
+    ("a != b == c", "(== (!= a b) c)"),
+    ("a > b >= c", "(>= (> a b) c)"),
+    ("a <= b + 1", "(<= a (+ b 1))"),
+    ("a + b < c * d", "(< (+ a b) (* c d))"),
+    // Logical
+    ("a or b and c", "(or a (and b c))"),
+    ("a and b or c", "(or (and a b) c)"),
+    ("a or b or c", "(or (or a b) c)"),
+    ("a == b and c != d", "(and (== a b) (!= c d))"),
+    ("!a and b", "(and (! a) b)"),
+    // Assignment
+    ("a = b or c", "(= a (or b c))"),
+    ("a = b = c", "(= a (= b c))"),
+    ("a = b + c * d", "(= a (+ b (* c d)))"),
+    // Unary
+    ("-a * b", "(* (- a) b)"),
+    ("!a == b", "(== (! a) b)"),
+    ("- -a", "(- (- a))"),
+    ("-a.b", "(- (. a b))"),
+    ("-f(x)", "(- (call f x))"),
+    // Calls and field accesses
+    ("a.b.c(1)", "(call (. (. a b) c) 1)"),
+    ("f(x).y", "(. (call f x) y)"),
+    // Pipes and `where`
+    ("a + b |> f", "(call f (+ a b))"),
+    ("a |> f or b", "(call (or f b) a)"),
+    ("x = a |> f", "(= x (call f a))"),
+    ("a + b where a = 1", "(where (+ a b) (a 1))"),
+];
+
+#[test]
+fn operators_group_by_precedence() {
+    for (source, expected) in CASES {
+        let (expr, _) = Parser::new(source, "example").parse_expression(1).unwrap();
+        assert_eq!(to_sexpr(&expr), *expected, "parsing {source:?}");
+    }
+}
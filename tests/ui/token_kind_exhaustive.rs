@@ -0,0 +1,77 @@
+// Every token is listed, but `TokenKind` is `#[non_exhaustive]`, so without
+// a `_` arm this still doesn't compile.
+
+use alpaca::tokens::TokenKind;
+
+fn name(kind: &TokenKind) -> &'static str {
+    match kind {
+        TokenKind::OpenParen => "token",
+        TokenKind::CloseParen => "token",
+        TokenKind::OpenBracket => "token",
+        TokenKind::CloseBracket => "token",
+        TokenKind::OpenBrace => "token",
+        TokenKind::CloseBrace => "token",
+        TokenKind::Comma => "token",
+        TokenKind::Semicolon => "token",
+        TokenKind::Dot => "token",
+        TokenKind::DotDot => "token",
+        TokenKind::DotDotEqual => "token",
+        TokenKind::Ellipsis => "token",
+        TokenKind::Colon => "token",
+        TokenKind::ColonColon => "token",
+        TokenKind::Arrow => "token",
+        TokenKind::Equal => "token",
+        TokenKind::EqualEqual => "token",
+        TokenKind::Bang => "token",
+        TokenKind::BangEqual => "token",
+        TokenKind::Greater => "token",
+        TokenKind::GreaterEqual => "token",
+        TokenKind::GreaterGreater => "token",
+        TokenKind::Less => "token",
+        TokenKind::LessEqual => "token",
+        TokenKind::LessLess => "token",
+        TokenKind::Plus => "token",
+        TokenKind::Minus => "token",
+        TokenKind::Star => "token",
+        TokenKind::StarStar => "token",
+        TokenKind::Slash => "token",
+        TokenKind::SlashSlash => "token",
+        TokenKind::Pipe => "token",
+        TokenKind::Question => "token",
+        TokenKind::Operator(_) => "token",
+        TokenKind::String(_) => "token",
+        TokenKind::Integer(_) => "token",
+        TokenKind::Ident(_) => "token",
+        TokenKind::Label(_) => "token",
+        TokenKind::And => "token",
+        TokenKind::Assert => "token",
+        TokenKind::Break => "token",
+        TokenKind::Continue => "token",
+        TokenKind::Do => "token",
+        TokenKind::Else => "token",
+        TokenKind::End => "token",
+        TokenKind::False => "token",
+        TokenKind::For => "token",
+        TokenKind::Fun => "token",
+        TokenKind::If => "token",
+        TokenKind::Let => "token",
+        TokenKind::Match => "token",
+        TokenKind::Mut => "token",
+        TokenKind::Or => "token",
+        TokenKind::Pub => "token",
+        TokenKind::Ref => "token",
+        TokenKind::Return => "token",
+        TokenKind::True => "token",
+        TokenKind::Type => "token",
+        TokenKind::Where => "token",
+        TokenKind::While => "token",
+        TokenKind::DocComment(_) => "token",
+        TokenKind::Comment(_) => "token",
+        TokenKind::Error(_) => "token",
+        TokenKind::EoF => "token",
+    }
+}
+
+fn main() {
+    let _ = name(&TokenKind::EoF);
+}
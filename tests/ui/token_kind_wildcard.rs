@@ -0,0 +1,16 @@
+// With a `_` arm, matching on `TokenKind` from outside the crate compiles.
+
+use alpaca::tokens::TokenKind;
+
+fn name(kind: &TokenKind) -> &'static str {
+    match kind {
+        TokenKind::Ident(_) => "identifier",
+        TokenKind::EoF => "end of file",
+        _ => "token",
+    }
+}
+
+fn main() {
+    assert_eq!(name(&TokenKind::EoF), "end of file");
+    assert_eq!(name(&TokenKind::Plus), "token");
+}